@@ -30,6 +30,13 @@ if [ -z "$SCHEDULE_DATE" ]; then
     exit 1
 fi
 
+# Refuse to schedule a job whose auth token will have expired by release time
+if ! "$PROJECT_ROOT/target/release/resy-rust" ping --valid-at "$SCHEDULE_TIME"; then
+    echo ""
+    echo "Error: Not scheduling - refresh your auth token first."
+    exit 1
+fi
+
 # Create launchd plist
 PLIST_NAME="com.resy.booking.$(date +%s)"
 PLIST_PATH="$HOME/Library/LaunchAgents/${PLIST_NAME}.plist"
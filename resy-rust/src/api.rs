@@ -1,23 +1,320 @@
 use anyhow::{Context, Result};
-use reqwest::{Client, header};
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use reqwest::{Client, RequestBuilder, Response, StatusCode, header};
+use serde::Deserialize;
 use serde_json::json;
 use urlencoding::encode;
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::time::{Duration, Instant};
+use tokio::task::JoinSet;
 use tokio::time::sleep;
 
+use crate::breaker::{self, CircuitBreaker, FatalError, FatalReason};
+use crate::config::NetworkConfig;
+use crate::credentials::Credentials;
+use crate::dns::PinnedHost;
+use crate::notify::{Event, Notifier};
+use crate::poll::{PollPhase, PollSchedule};
+use crate::policy::{BookingPolicy, PolicyLimits, PolicyViolation};
+use crate::retry::RetryPolicy;
+use crate::ratelimit::{self, BudgetExhausted, RateLimiter};
+use crate::token::TokenStatus;
 use crate::types::*;
 use crate::LoggerHandle;
 
+/// How many times a 429 is waited out and resent before it's returned to the caller
+const MAX_RATE_LIMIT_RETRIES: usize = 3;
+
+/// Booking defaults shared by the CLI, job files and scheduled jobs
+pub const DEFAULT_THREADS: usize = 5;
+pub const DEFAULT_RETRIES: usize = 5;
+pub const DEFAULT_POLL_INTERVAL_MS: u64 = 250;
+pub const DEFAULT_POLL_TIMEOUT_SECS: u64 = 120;
+
+/// How far a dry run goes before stopping short of booking
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum DryRun {
+    /// List matching slots and their booking policies
+    Shallow,
+    /// Also get a real booking token, check the policy and payment method,
+    /// and show the /3/book request that would be sent
+    Deep,
+}
+
+/// Everything the booking engine needs to know about one booking run
+#[derive(Debug, Clone)]
+pub struct BookingParams {
+    pub venue_id: String,
+    pub party_size: u32,
+    pub day: String,
+    pub times: Vec<String>,
+    pub types: Vec<String>,
+    /// Stop short of booking when set
+    pub dry_run: Option<DryRun>,
+    pub num_threads: usize,
+    /// Retries of each slot per booking thread after the first attempt
+    pub num_retries: usize,
+    /// Backoff and retryable errors for polling and booking
+    pub retry: RetryPolicy,
+    /// Poll interval inside the release window
+    pub poll_interval: Duration,
+    /// How long to poll, counted from the release if polling starts before it
+    pub poll_timeout: Duration,
+    /// How the poll interval changes around the release
+    pub poll_schedule: PollSchedule,
+    /// Predicted release instant by our clock; polling starts at the release window when unset
+    pub release_at: Option<DateTime<Utc>>,
+    /// Card to book with; defaults to the account's default payment method
+    pub payment_method_id: Option<u64>,
+    /// Slots whose cancellation fee or deposit exceed these are skipped
+    pub policy_limits: PolicyLimits,
+}
+
+/// A payment method ID belongs to a single account, so it can't be used when
+/// booking with several
+pub fn check_payment_method_accounts(payment_method_id: Option<u64>, accounts: usize) -> Result<()> {
+    if payment_method_id.is_some() && accounts > 1 {
+        anyhow::bail!("A payment method ID belongs to one account; it can't be used with {} profiles", accounts);
+    }
+    Ok(())
+}
+
+/// A non-success HTTP status from the Resy API
+#[derive(Debug)]
+pub struct HttpError {
+    /// What was being attempted, e.g. "Failed to fetch slots"
+    pub action: &'static str,
+    pub status: StatusCode,
+}
+
+impl HttpError {
+    fn new(action: &'static str, status: StatusCode) -> Self {
+        Self { action, status }
+    }
+}
+
+impl std::fmt::Display for HttpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.action, self.status)
+    }
+}
+
+impl std::error::Error for HttpError {}
+
+/// Resy says the slot was taken by someone else; retrying it is pointless
+#[derive(Debug)]
+pub struct SlotTaken {
+    pub status: StatusCode,
+}
+
+impl std::fmt::Display for SlotTaken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Slot is no longer available ({})", self.status)
+    }
+}
+
+impl std::error::Error for SlotTaken {}
+
+/// Phrases Resy uses when a slot has already gone. Each names the slot or
+/// reservation, so other rejections (e.g. a payment method that isn't
+/// available) aren't mistaken for it.
+const SLOT_TAKEN_PHRASES: &[&str] = &[
+    "slot is no longer available",
+    "slot no longer available",
+    "slot is not available",
+    "slot is unavailable",
+    "slot is taken",
+    "slot has been taken",
+    "slot has already been booked",
+    "reservation is no longer available",
+    "reservation no longer available",
+    "reservation is not available",
+    "table is no longer available",
+    "time is no longer available",
+];
+
+/// Whether a rejected response says the slot is gone
+fn is_slot_taken(status: StatusCode, body: &str) -> bool {
+    let body = body.to_lowercase();
+    status.is_client_error() && SLOT_TAKEN_PHRASES.iter().any(|phrase| body.contains(phrase))
+}
+
+/// The error for a failed /3/details or /3/book response, telling a taken slot
+/// apart from other failures by the message, since the status alone doesn't say
+async fn booking_rejection(action: &'static str, response: Response) -> anyhow::Error {
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    if is_slot_taken(status, &body) {
+        SlotTaken { status }.into()
+    } else {
+        HttpError::new(action, status).into()
+    }
+}
+
+/// The form fields /3/book takes, before URL encoding
+fn book_form(book_token: &str, payment_id: Option<u64>) -> Vec<(&'static str, String)> {
+    let mut form = vec![("book_token", book_token.to_string())];
+    if let Some(id) = payment_id {
+        form.push(("struct_payment_method", json!({"id": id}).to_string()));
+    }
+    form
+}
+
+/// Every slot a booking round tried was taken or over the policy limits
+#[derive(Debug)]
+pub struct AllSlotsTaken(pub usize);
+
+impl std::fmt::Display for AllSlotsTaken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "None of the {} matching slots can be booked (taken or over the policy limits)", self.0)
+    }
+}
+
+impl std::error::Error for AllSlotsTaken {}
+
+/// Slot tokens that can't be booked any more, shared by every booking thread
+type DeadSlots = Arc<std::sync::Mutex<HashSet<String>>>;
+
+/// Raises its flag when dropped, so booking threads see that the race they belong
+/// to was abandoned (e.g. a daemon job was cancelled or paused) and stop booking
+struct CancelOnDrop(Arc<AtomicBool>);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Account state shared by every client forked from the same credentials
+struct Session {
+    credentials: Credentials,
+    /// Current auth token; replaced when a 401 triggers a password re-login
+    auth_token: RwLock<String>,
+    refresh_lock: tokio::sync::Mutex<()>,
+    /// Set after a failed re-login so a bad password isn't retried on every 401
+    refresh_failed: AtomicBool,
+}
+
+#[derive(Clone)]
 pub struct ResyClient {
     client: Client,
-    api_key: String,
-    auth_token: String,
+    network: Arc<NetworkConfig>,
+    /// API host addresses to connect to instead of looking them up
+    pinned: Option<Arc<PinnedHost>>,
+    session: Arc<Session>,
+    notifier: Notifier,
+    stats: Arc<EngineStats>,
+    limiter: Arc<RateLimiter>,
+    breaker: Arc<CircuitBreaker>,
+}
+
+/// Request counters shared by a client and its forks, for live progress reports
+#[derive(Debug, Default)]
+pub struct EngineStats {
+    /// Slot searches (/4/find)
+    pub polls: AtomicUsize,
+    /// Attempts to book a slot (/3/details + /3/book)
+    pub booking_attempts: AtomicUsize,
+}
+
+/// The outcome of a successful booking run
+#[derive(Debug, Clone)]
+pub struct BookedSlot {
+    pub slot: Slot,
+    /// Profile of the account that won the booking
+    pub profile: String,
+    pub confirmation: BookResponse,
 }
 
 impl ResyClient {
-    pub fn new(api_key: String, auth_token: String) -> Result<Self> {
+    pub fn new(credentials: Credentials) -> Result<Self> {
+        let session = Session {
+            auth_token: RwLock::new(credentials.auth_token.clone()),
+            credentials,
+            refresh_lock: tokio::sync::Mutex::new(()),
+            refresh_failed: AtomicBool::new(false),
+        };
+
+        let network = NetworkConfig::default();
+        Ok(Self {
+            client: Self::build_http_client(&network, None)?,
+            network: Arc::new(network),
+            pinned: None,
+            session: Arc::new(session),
+            notifier: Notifier::default(),
+            stats: Arc::default(),
+            limiter: Arc::default(),
+            breaker: Arc::default(),
+        })
+    }
+
+    /// Report booking events (and rejected credentials) to `notifier`
+    pub fn with_notifier(mut self, notifier: Notifier) -> Self {
+        self.notifier = notifier;
+        self
+    }
+
+    pub fn with_stats(mut self, stats: Arc<EngineStats>) -> Self {
+        self.stats = stats;
+        self
+    }
+
+    /// Send requests to `network`'s API base, over HTTP/2 if it asks for it
+    pub fn with_network(mut self, network: &NetworkConfig) -> Result<Self> {
+        self.client = Self::build_http_client(network, self.pinned.as_deref())?;
+        self.network = Arc::new(network.clone());
+        Ok(self)
+    }
+
+    /// Connect to `pinned`'s addresses, preferred first, instead of resolving the API host
+    pub fn with_pinned_host(mut self, pinned: Arc<PinnedHost>) -> Result<Self> {
+        self.client = Self::build_http_client(&self.network, Some(&pinned))?;
+        self.pinned = Some(pinned);
+        Ok(self)
+    }
+
+    /// Share `limiter`'s rate and request budget with every other client using it
+    pub fn with_rate_limiter(mut self, limiter: Arc<RateLimiter>) -> Self {
+        self.limiter = limiter;
+        self
+    }
+
+    /// Share `breaker`, so a fatal error seen by any client using it stops them all
+    pub fn with_breaker(mut self, breaker: Arc<CircuitBreaker>) -> Self {
+        self.breaker = breaker;
+        self
+    }
+
+    /// The first of `accounts` (this client if empty) whose circuit breaker
+    /// hasn't tripped, or the fatal error once every one of them has
+    pub fn live_account<'a>(&'a self, accounts: &'a [ResyClient]) -> Result<&'a ResyClient> {
+        let accounts = if accounts.is_empty() { std::slice::from_ref(self) } else { accounts };
+        match accounts.iter().find(|account| account.breaker.tripped().is_none()) {
+            Some(account) => Ok(account),
+            None => accounts[0].breaker.check().map(|_| &accounts[0]),
+        }
+    }
+
+    /// A client with its own connection pool that shares this client's account session
+    pub fn fork(&self) -> Result<Self> {
+        Ok(Self {
+            client: Self::build_http_client(&self.network, self.pinned.as_deref())?,
+            network: Arc::clone(&self.network),
+            pinned: self.pinned.clone(),
+            session: Arc::clone(&self.session),
+            notifier: self.notifier.clone(),
+            stats: Arc::clone(&self.stats),
+            limiter: Arc::clone(&self.limiter),
+            breaker: Arc::clone(&self.breaker),
+        })
+    }
+
+    /// The HTTP client every request goes through; `latency` probes use it too
+    pub fn build_http_client(network: &NetworkConfig, pinned: Option<&PinnedHost>) -> Result<Client> {
         let mut headers = header::HeaderMap::new();
         headers.insert(
             "user-agent",
@@ -30,73 +327,277 @@ impl ResyClient {
         headers.insert("cache-control", "no-cache".parse()?);
 
         // Optimize for low latency: connection pooling, TCP optimizations, shorter timeout
-        let client = Client::builder()
+        let mut builder = Client::builder()
             .default_headers(headers)
             .pool_max_idle_per_host(10)
             .pool_idle_timeout(Duration::from_secs(90))
-            .http1_only()  // Force HTTP/1.1 to avoid HTTP/2 issues with WAF
             .tcp_nodelay(true)
             .timeout(Duration::from_secs(2))
-            .connect_timeout(Duration::from_millis(500))
-            .build()?;
+            .connect_timeout(Duration::from_millis(500));
+        if !network.http2 {
+            builder = builder.http1_only();  // Force HTTP/1.1 to avoid HTTP/2 issues with WAF
+        }
+        if let Some(pinned) = pinned {
+            builder = builder.resolve_to_addrs(&pinned.host, &pinned.addrs());
+        }
 
-        Ok(Self {
-            client,
-            api_key,
-            auth_token,
-        })
+        Ok(builder.build()?)
+    }
+
+    pub fn network(&self) -> &NetworkConfig {
+        &self.network
+    }
+
+    /// Full URL of an API path such as "/2/user"
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.network.base(), path)
+    }
+
+    pub fn profile(&self) -> &str {
+        &self.session.credentials.profile
+    }
+
+    pub fn notifier(&self) -> &Notifier {
+        &self.notifier
+    }
+
+    pub fn auth_token(&self) -> String {
+        self.session.auth_token.read().unwrap().clone()
     }
 
-    fn auth_headers(&self) -> header::HeaderMap {
+    /// Expiry status of this client's auth token, decoded locally
+    pub fn token_status(&self) -> TokenStatus {
+        TokenStatus::now(&self.auth_token())
+    }
+
+    fn api_key_header(&self) -> header::HeaderMap {
         let mut headers = header::HeaderMap::new();
-        let api_key = self.api_key.trim().trim_matches('"').trim_matches('\'');
-        let auth_token = self.auth_token.trim().trim_matches('"').trim_matches('\'');
-        
+        let api_key = self.session.credentials.api_key.trim().trim_matches('"').trim_matches('\'');
+
         headers.insert(
             "authorization",
             format!(r#"ResyAPI api_key="{}""#, api_key).parse().unwrap(),
         );
+        headers
+    }
+
+    fn auth_headers(&self, auth_token: &str) -> header::HeaderMap {
+        let mut headers = self.api_key_header();
+        let auth_token = auth_token.trim().trim_matches('"').trim_matches('\'');
+        
         headers.insert("x-resy-auth-token", auth_token.parse().unwrap());
         headers.insert("x-resy-universal-auth", auth_token.parse().unwrap());
         headers
     }
 
+    /// Send an authenticated request. On a 401, re-login with the account's
+    /// email/password (if configured) and resend once with the new token.
+    /// A rejection no retry can fix trips the circuit breaker and fails this and
+    /// every later request with a FatalError.
+    async fn send<F>(&self, build: F) -> Result<Response>
+    where
+        F: Fn(&Client) -> RequestBuilder,
+    {
+        self.breaker.check()?;
+        let token = self.auth_token();
+        let response = self.send_limited(|| build(&self.client).headers(self.auth_headers(&token))).await?;
+
+        let response = if response.status() != StatusCode::UNAUTHORIZED {
+            response
+        } else {
+            match self.refresh_auth(&token).await {
+                Ok(true) => {
+                    let token = self.auth_token();
+                    self.send_limited(|| build(&self.client).headers(self.auth_headers(&token))).await?
+                }
+                Ok(false) => response,
+                Err(e) => {
+                    // Only the first failure gets here; later 401s skip the re-login
+                    let e = e.context(format!("Re-login for profile '{}' failed", self.profile()));
+                    self.notifier.log(&format!("⚠️  {:#}", e));
+                    self.notifier.emit(Event::auth_error(self.profile(), &e));
+                    response
+                }
+            }
+        };
+        self.check_fatal(response).await
+    }
+
+    /// Trip the breaker on a fatal auth rejection; pass other responses through,
+    /// body included, for the caller to interpret
+    async fn check_fatal(&self, response: Response) -> Result<Response> {
+        let status = response.status();
+        if !matches!(status.as_u16(), 401 | 403 | 419) {
+            return Ok(response);
+        }
+
+        // Reading the body consumes the response, so keep what's needed to rebuild it
+        let mut rebuilt = http::Response::builder().status(status).version(response.version());
+        if let Some(headers) = rebuilt.headers_mut() {
+            *headers = response.headers().clone();
+        }
+        let body = response.bytes().await.unwrap_or_default();
+        let Some(reason) = FatalReason::classify(status, &String::from_utf8_lossy(&body)) else {
+            return Ok(rebuilt.body(body)?.into());
+        };
+        let error = FatalError {
+            reason,
+            profile: self.profile().to_string(),
+            status,
+        };
+        if self.breaker.trip(error.clone()) {
+            self.notifier.emit(Event::auth_error(self.profile(), &anyhow::Error::from(error.clone())));
+        }
+        Err(error.into())
+    }
+
+    /// Send once the rate limiter allows it. A 429 slows the limiter down for
+    /// everyone and is resent after its Retry-After, up to MAX_RATE_LIMIT_RETRIES times.
+    async fn send_limited<F>(&self, build: F) -> Result<Response>
+    where
+        F: Fn() -> RequestBuilder,
+    {
+        let mut throttled = 0;
+        loop {
+            self.limiter.acquire().await?;
+            let response = build().send().await?;
+            if response.status() != StatusCode::TOO_MANY_REQUESTS {
+                self.limiter.on_success();
+                return Ok(response);
+            }
+
+            self.limiter.on_rate_limited(ratelimit::retry_after(response.headers()));
+            throttled += 1;
+            if throttled > MAX_RATE_LIMIT_RETRIES {
+                return Ok(response);
+            }
+        }
+    }
+
+    /// Replace a rejected auth token via password login. Returns false if
+    /// re-login isn't possible for this account.
+    async fn refresh_auth(&self, stale_token: &str) -> Result<bool> {
+        let Some(login) = &self.session.credentials.login else {
+            return Ok(false);
+        };
+        if self.session.refresh_failed.load(Ordering::Relaxed) {
+            return Ok(false);
+        }
+
+        let _guard = self.session.refresh_lock.lock().await;
+
+        // Another task already refreshed while we waited for the lock
+        if self.auth_token() != stale_token {
+            return Ok(true);
+        }
+
+        match self.password_login(&login.email, &login.password).await {
+            Ok(response) => {
+                *self.session.auth_token.write().unwrap() = response.token;
+                Ok(true)
+            }
+            Err(e) => {
+                self.session.refresh_failed.store(true, Ordering::Relaxed);
+                Err(e)
+            }
+        }
+    }
+
+    /// Exchange an email and password for an auth token
+    pub async fn password_login(&self, email: &str, password: &str) -> Result<LoginResponse> {
+        let form_data = format!("email={}&password={}", encode(email), encode(password));
+
+        let response = self
+            .send_limited(|| {
+                self.client
+                    .post(self.url("/3/auth/password"))
+                    .headers(self.api_key_header())
+                    .header("content-type", "application/x-www-form-urlencoded")
+                    .body(form_data.clone())
+            })
+            .await
+            .context("Failed to log in")?;
+
+        if !response.status().is_success() {
+            return Err(HttpError::new("Failed to log in", response.status()).into());
+        }
+
+        response.json().await.context("Failed to parse login response")
+    }
+
     pub async fn fetch_venue_details(&self, venue_id: &str) -> Result<VenueResponse> {
-        let url = format!("https://api.resy.com/2/config?venue_id={}", venue_id);
-        let response = self.client
-            .get(&url)
-            .headers(self.auth_headers())
-            .send()
+        let url = self.url(&format!("/2/config?venue_id={}", venue_id));
+        let response = self
+            .send(|client| client.get(&url))
             .await
             .context("Failed to fetch venue details")?;
         
         if !response.status().is_success() {
-            anyhow::bail!("Failed to fetch venue details: {}", response.status());
+            return Err(HttpError::new("Failed to fetch venue details", response.status()).into());
         }
         
         response.json().await.context("Failed to parse venue response")
     }
 
+    /// How far Resy's clock is ahead of ours, estimated from the `Date` header of a
+    /// round trip. The header has one-second resolution, so this is only good to ±0.5s.
+    pub async fn clock_offset(&self) -> Result<chrono::Duration> {
+        self.limiter.acquire().await?;
+        let sent = chrono::Utc::now();
+        let response = self
+            .client
+            .head(self.url("/"))
+            .send()
+            .await
+            .context("Failed to reach the Resy API")?;
+        let received = chrono::Utc::now();
+
+        let date = response
+            .headers()
+            .get(header::DATE)
+            .context("Resy response has no Date header")?
+            .to_str()?;
+        let server = chrono::DateTime::parse_from_rfc2822(date)
+            .context(format!("Invalid Date header: {}", date))?
+            .with_timezone(&chrono::Utc);
+
+        // The header is truncated to the second, so aim for the middle of it
+        let server = server + chrono::Duration::milliseconds(500);
+        Ok(server - (sent + (received - sent) / 2))
+    }
+
+    pub async fn fetch_user(&self) -> Result<UserResponse> {
+        let response = self
+            .send(|client| client.get(self.url("/2/user")))
+            .await
+            .context("Failed to reach the Resy API")?;
+
+        if !response.status().is_success() {
+            return Err(HttpError::new("Could not authenticate with Resy", response.status()).into());
+        }
+
+        response.json().await.context("Failed to parse user response")
+    }
+
     pub async fn fetch_slots(
         &self,
         venue_id: &str,
         party_size: u32,
         day: &str,
     ) -> Result<Vec<Slot>> {
-        let url = format!(
-            "https://api.resy.com/4/find?party_size={}&venue_id={}&day={}&lat=0&long=0",
+        let url = self.url(&format!(
+            "/4/find?party_size={}&venue_id={}&day={}&lat=0&long=0",
             party_size, venue_id, day
-        );
+        ));
+        self.stats.polls.fetch_add(1, Ordering::Relaxed);
         
-        let response = self.client
-            .get(&url)
-            .headers(self.auth_headers())
-            .send()
+        let response = self
+            .send(|client| client.get(&url))
             .await
             .context("Failed to fetch slots")?;
 
         if !response.status().is_success() {
-            anyhow::bail!("Failed to fetch slots: {}", response.status());
+            return Err(HttpError::new("Failed to fetch slots", response.status()).into());
         }
 
         let find_response: FindResponse = response.json().await.context("Failed to parse slots response")?;
@@ -114,90 +615,181 @@ impl ResyClient {
         day: &str,
         party_size: u32,
     ) -> Result<DetailsResponse> {
-        let url = "https://api.resy.com/3/details";
+        let url = self.url("/3/details");
         let body = BookingConfig {
             config_id: config_id.to_string(),
             day: day.to_string(),
             party_size,
         };
 
-        let response = self.client
-            .post(url)
-            .headers(self.auth_headers())
-            .json(&body)
-            .send()
+        let response = self
+            .send(|client| client.post(&url).json(&body))
             .await
             .context("Failed to get booking token")?;
 
         if !response.status().is_success() {
-            anyhow::bail!("Failed to get booking token: {}", response.status());
+            return Err(booking_rejection("Failed to get booking token", response).await);
         }
 
         response.json().await.context("Failed to parse booking details")
     }
 
-    pub async fn book_reservation(&self, book_token: &str, payment_id: Option<u64>) -> Result<()> {
-        let url = "https://api.resy.com/3/book";
-        let mut form_data = format!("book_token={}", encode(book_token));
-        
-        if let Some(id) = payment_id {
-            let payment_json = json!({"id": id}).to_string();
-            form_data.push_str(&format!("&struct_payment_method={}", encode(&payment_json)));
-        }
+    pub async fn book_reservation(&self, book_token: &str, payment_id: Option<u64>) -> Result<BookResponse> {
+        let url = self.url("/3/book");
+        let form_data = book_form(book_token, payment_id)
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, encode(value)))
+            .collect::<Vec<_>>()
+            .join("&");
 
-        let response = self.client
-            .post(url)
-            .headers(self.auth_headers())
-            .header("content-type", "application/x-www-form-urlencoded")
-            .body(form_data)
-            .send()
+        let response = self
+            .send(|client| {
+                client
+                    .post(&url)
+                    .header("content-type", "application/x-www-form-urlencoded")
+                    .body(form_data.clone())
+            })
             .await
             .context("Failed to book reservation")?;
 
         if !response.status().is_success() {
-            anyhow::bail!("Failed to book reservation: {}", response.status());
+            return Err(booking_rejection("Failed to book reservation", response).await);
         }
 
-        Ok(())
+        // The booking went through even if the confirmation can't be parsed
+        Ok(response.json().await.unwrap_or_default())
     }
 
-
-    async fn try_book_slot(&self, slot: &Slot, day: &str, party_size: u32) -> Result<()> {
+    /// Everything short of /3/book: a booking token for the slot, checked
+    /// against the policy limits, and the payment method to book with
+    async fn prepare_booking(
+        &self,
+        slot: &Slot,
+        day: &str,
+        party_size: u32,
+        payment_method_id: Option<u64>,
+        policy_limits: &PolicyLimits,
+    ) -> Result<(DetailsResponse, Option<u64>)> {
         let details = self.get_booking_token(&slot.config.token, day, party_size).await?;
-        
-        let payment_id = details.user.payment_methods
-            .as_ref()
-            .and_then(|methods| methods.first())
+        BookingPolicy::from_details(&details).check(policy_limits)?;
+
+        let payment_id = details.user
+            .select_payment_method(payment_method_id)?
             .map(|method| method.id);
+        Ok((details, payment_id))
+    }
+
+    async fn try_book_slot(
+        &self,
+        slot: &Slot,
+        day: &str,
+        party_size: u32,
+        payment_method_id: Option<u64>,
+        policy_limits: &PolicyLimits,
+    ) -> Result<BookResponse> {
+        self.stats.booking_attempts.fetch_add(1, Ordering::Relaxed);
+        let (details, payment_id) = self
+            .prepare_booking(slot, day, party_size, payment_method_id, policy_limits)
+            .await?;
 
         self.book_reservation(&details.book_token.value, payment_id).await
     }
 
-    /// Poll for available slots with configurable interval and timeout
+    /// Go through every booking step but /3/book for the first slot that gets
+    /// that far, and log the request it would have received. Fails if no slot does.
+    pub async fn deep_dry_run(&self, params: &BookingParams, slots: &[Slot], logger: &LoggerHandle) -> Result<()> {
+        logger.log(&format!("🔬 Deep dry run as '{}':", self.profile()));
+        for slot in slots {
+            logger.log(&format!("   {} ({}):", slot.date.start, slot.config.slot_type));
+            let prepared = self
+                .prepare_booking(slot, &params.day, params.party_size, params.payment_method_id, &params.policy_limits)
+                .await;
+            let (details, payment_id) = match prepared {
+                Ok(prepared) => prepared,
+                Err(e) if breaker::is_fatal(&e) || e.is::<BudgetExhausted>() => return Err(e),
+                Err(e) => {
+                    let icon = if e.is::<PolicyViolation>() { "⛔" } else { "⚠️ " };
+                    logger.log(&format!("      {} {}", icon, e));
+                    continue;
+                }
+            };
+
+            logger.log("      ✅ Booking token issued");
+            for line in BookingPolicy::from_details(&details).describe() {
+                logger.log(&format!("      {}", line));
+            }
+            let method = payment_id.and_then(|id| {
+                details.user.payment_methods.as_deref().unwrap_or_default().iter().find(|method| method.id == id)
+            });
+            match method {
+                Some(method) => logger.log(&format!("      💳 Payment method: {} ({})", method.id, method.describe())),
+                None => logger.log("      💳 Payment method: none on this account"),
+            }
+            logger.log(&format!("      📤 POST {} would receive:", self.url("/3/book")));
+            for (key, value) in book_form(&details.book_token.value, payment_id) {
+                logger.log(&format!("         {} = {}", key, value));
+            }
+            logger.log("🏃 Dry run mode - stopping before /3/book; the token will expire unused");
+            return Ok(());
+        }
+
+        anyhow::bail!("Deep dry run: none of the {} matching slot(s) could be booked", slots.len())
+    }
+
+    /// Fetch /3/details for each slot and log its cancellation/deposit terms
+    async fn log_slot_policies(&self, params: &BookingParams, slots: &[Slot], logger: &LoggerHandle) {
+        logger.log("📜 Booking policies:");
+        for slot in slots {
+            logger.log(&format!("   {} ({}):", slot.date.start, slot.config.slot_type));
+            match self.get_booking_token(&slot.config.token, &params.day, params.party_size).await {
+                Ok(details) => {
+                    let policy = BookingPolicy::from_details(&details);
+                    for line in policy.describe() {
+                        logger.log(&format!("      {}", line));
+                    }
+                    if let Err(violation) = policy.check(&params.policy_limits) {
+                        logger.log(&format!("      ⛔ {}", violation));
+                    }
+                }
+                Err(e) => logger.log(&format!("      ⚠️  {}", e)),
+            }
+        }
+    }
+
+    /// Poll for available slots other than `dead` ones until `deadline`, pacing
+    /// polls by the params' poll schedule
     async fn poll_for_slots(
         &self,
-        venue_id: &str,
-        party_size: u32,
-        day: &str,
-        times: &[String],
-        types: &[String],
-        poll_interval: Duration,
-        poll_timeout: Duration,
+        params: &BookingParams,
+        deadline: Instant,
+        dead: &DeadSlots,
         logger: &LoggerHandle,
     ) -> Result<Vec<Slot>> {
+        let schedule = params.poll_schedule;
+        let release = params.release_at.unwrap_or_else(Utc::now);
         let start = Instant::now();
         let mut attempt = 0;
+        let mut phase = None;
+        let mut backoff_polls = 0;
+        let mut last_progress = Instant::now();
+        let mut consecutive_errors = 0;
+        let mut failing_since = None;
 
         loop {
             attempt += 1;
             
             // Try to fetch slots
-            match self.fetch_slots(venue_id, party_size, day).await {
+            match self.fetch_slots(&params.venue_id, params.party_size, &params.day).await {
                 Ok(slots) => {
+                    consecutive_errors = 0;
+                    failing_since = None;
+                    let dead = dead.lock().unwrap();
                     let matching: Vec<_> = slots
                         .into_iter()
-                        .filter(|slot| slot.matches(times, types))
+                        .filter(|slot| slot.matches(&params.times, &params.types))
+                        .filter(|slot| !dead.contains(&slot.config.token))
                         .collect();
+                    drop(dead);
                     
                     if !matching.is_empty() {
                         logger.log(&format!("✅ Found {} matching slots after {} attempts ({:.2}s)", 
@@ -205,159 +797,451 @@ impl ResyClient {
                         return Ok(matching);
                     }
                 }
+                Err(e) if breaker::is_fatal(&e) => {
+                    logger.log(&format!("🛑 Stopped polling after {} attempts", attempt));
+                    return Err(e);
+                }
+                Err(e) if e.downcast_ref::<BudgetExhausted>().is_some() => {
+                    anyhow::bail!("❌ Stopped polling after {} attempts: {}", attempt, e);
+                }
+                Err(e) if !params.retry.is_retryable(&e) => {
+                    anyhow::bail!("❌ Stopped polling after {} attempts: {}", attempt, e);
+                }
                 Err(e) => {
-                    // Continue polling even on errors (restaurant might not have released slots yet)
+                    // Continue polling on retryable errors (restaurant might not have released slots yet)
                     if attempt == 1 {
                         logger.log(&format!("⏳ Polling for slots... ({})", e));
                     }
+                    consecutive_errors += 1;
+                    if params.retry.out_of_time(*failing_since.get_or_insert_with(Instant::now)) {
+                        anyhow::bail!("❌ Stopped polling after {} consecutive errors: {}", consecutive_errors, e);
+                    }
                 }
             }
 
             // Check timeout
-            if start.elapsed() >= poll_timeout {
+            if Instant::now() >= deadline {
                 anyhow::bail!(
                     "❌ No matching slots found after {:.1}s of polling ({} attempts)", 
-                    poll_timeout.as_secs_f64(), 
+                    start.elapsed().as_secs_f64(), 
                     attempt
                 );
             }
 
+            let now = Utc::now();
+            let current = schedule.phase(now, release);
+            if current == PollPhase::Backoff {
+                backoff_polls += 1;
+            }
+            let mut delay = schedule.next_delay(now, release, params.poll_interval, backoff_polls);
+            if consecutive_errors > 0 {
+                delay = delay.max(params.retry.delay(consecutive_errors - 1));
+            }
+            if phase != Some(current) {
+                logger.log(&format!("⏱️  Polling every {}ms ({})", delay.as_millis(), current.as_str()));
+                phase = Some(current);
+            }
+
             // Show progress every 5 seconds
-            if attempt > 1 && start.elapsed().as_secs() % 5 == 0 && start.elapsed().as_millis() % 1000 < poll_interval.as_millis() {
+            if last_progress.elapsed() >= Duration::from_secs(5) {
                 logger.log(&format!("⏳ Still polling... ({:.1}s elapsed, {} attempts)", 
                     start.elapsed().as_secs_f64(), attempt));
+                last_progress = Instant::now();
             }
 
-            sleep(poll_interval).await;
+            sleep(delay.min(deadline.saturating_duration_since(Instant::now()))).await;
         }
     }
 
-    /// Competitive booking with concurrent threads and retries
+    /// Competitive booking with concurrent threads and retries. Polling and
+    /// booking alternate until a slot is booked or the poll timeout passes;
+    /// slots already tried go after new ones in each round.
+    ///
+    /// Booking threads are spread round-robin across `accounts`, so several
+    /// profiles can race for the same slot. Polling uses the first account whose
+    /// credentials haven't been rejected; the run only stops on a fatal error
+    /// once every account has hit one.
     pub async fn book_competitive(
         &self,
-        venue_id: &str,
-        party_size: u32,
-        day: &str,
-        times: &[String],
-        types: &[String],
-        dry_run: bool,
-        num_threads: usize,
-        num_retries: usize,
-        poll_interval: Duration,
-        poll_timeout: Duration,
+        params: &BookingParams,
+        accounts: &[ResyClient],
         logger: LoggerHandle,
-    ) -> Result<()> {
+    ) -> Result<Option<BookedSlot>> {
         logger.log("📍 Fetching venue details...");
-        let venue = self.fetch_venue_details(venue_id).await?;
-        logger.log(&format!("🍽️  Restaurant: {}", venue.venue.name));
+        let venue = loop {
+            let result = match self.live_account(accounts) {
+                Ok(account) => account.fetch_venue_details(&params.venue_id).await,
+                Err(e) => Err(e),
+            };
+            match result {
+                Err(e) if breaker::is_fatal(&e) && self.live_account(accounts).is_ok() => {
+                    logger.log(&format!("🔀 {:#}; trying another account", e));
+                }
+                result => break result,
+            }
+        };
+        let venue = match venue {
+            Ok(venue) => venue,
+            Err(e) => {
+                self.notifier.emit(Event::failed(params, &params.venue_id, &e));
+                return Err(e);
+            }
+        };
+        let venue_name = venue.venue.name;
+        logger.log(&format!("🍽️  Restaurant: {}", venue_name));
 
         logger.log("🔍 Polling for available slots...");
-        logger.log(&format!("   Poll interval: {}ms", poll_interval.as_millis()));
-        logger.log(&format!("   Poll timeout: {}s", poll_timeout.as_secs()));
+        logger.log(&format!("   Poll schedule: {}", params.poll_schedule.describe(params.poll_interval)));
+        if let Some(release) = params.release_at {
+            logger.log(&format!("   Predicted release: {}", release.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S%.3f")));
+        }
+        logger.log(&format!("   Poll timeout: {}s", params.poll_timeout.as_secs()));
+        let limits = self.limiter.limits();
+        match limits.request_budget {
+            Some(budget) => logger.log(&format!("   Rate limit: {} req/s (burst {}), budget {} requests",
+                limits.requests_per_sec, limits.burst, budget)),
+            None => logger.log(&format!("   Rate limit: {} req/s (burst {})", limits.requests_per_sec, limits.burst)),
+        }
         
-        let matching_slots = self.poll_for_slots(
-            venue_id,
-            party_size,
-            day,
-            times,
-            types,
-            poll_interval,
-            poll_timeout,
-            &logger,
-        ).await?;
-
-        logger.log("🎯 Available matching slots:");
-        for slot in &matching_slots {
-            logger.log(&format!("   - {} ({})", slot.date.start, slot.config.slot_type));
+        // The timeout runs from the release, not from early sparse polls
+        let until_release = params.release_at.map_or(Duration::ZERO, |release| (release - Utc::now()).to_std().unwrap_or_default());
+        let deadline = Instant::now() + until_release + params.poll_timeout;
+        let dead: DeadSlots = Arc::default();
+        // Tokens already raced for; they're tried again, but after any new slots
+        let mut tried: HashSet<String> = HashSet::new();
+        let mut round = 0;
+
+        loop {
+            let poller = match self.live_account(accounts) {
+                Ok(poller) => poller,
+                Err(e) => {
+                    self.notifier.emit(Event::failed(params, &venue_name, &e));
+                    return Err(e);
+                }
+            };
+            let mut matching_slots = match poller.poll_for_slots(params, deadline, &dead, &logger).await {
+                Ok(slots) => slots,
+                Err(e) if breaker::is_fatal(&e) && self.live_account(accounts).is_ok() => {
+                    logger.log(&format!("🔀 {}; polling with another account", e));
+                    continue;
+                }
+                Err(e) => {
+                    self.notifier.emit(Event::failed(params, &venue_name, &e));
+                    return Err(e);
+                }
+            };
+            // Only announce slots this run hasn't already raced for or given up on
+            let new_slots: Vec<Slot> = {
+                let dead = dead.lock().unwrap();
+                matching_slots
+                    .iter()
+                    .filter(|slot| !tried.contains(&slot.config.token) && !dead.contains(&slot.config.token))
+                    .cloned()
+                    .collect()
+            };
+            if !new_slots.is_empty() {
+                self.notifier.emit(Event::slots_found(params, &venue_name, &new_slots));
+            }
+
+            matching_slots.sort_by_key(|slot| tried.contains(&slot.config.token));
+            if round > 0 {
+                let new = matching_slots.iter().filter(|slot| !tried.contains(&slot.config.token)).count();
+                logger.log(&format!("🔄 Round {}: {} new slot(s), {} tried before", round + 1, new, matching_slots.len() - new));
+            }
+            tried.extend(matching_slots.iter().map(|slot| slot.config.token.clone()));
+
+            logger.log("🎯 Available matching slots:");
+            for slot in &matching_slots {
+                logger.log(&format!("   - {} ({})", slot.date.start, slot.config.slot_type));
+            }
+
+            match params.dry_run {
+                Some(DryRun::Shallow) => {
+                    poller.log_slot_policies(params, &matching_slots, &logger).await;
+                    logger.log("🏃 Dry run mode - skipping actual booking");
+                    return Ok(None);
+                }
+                Some(DryRun::Deep) => {
+                    return match poller.deep_dry_run(params, &matching_slots, &logger).await {
+                        Ok(()) => Ok(None),
+                        Err(e) => {
+                            self.notifier.emit(Event::failed(params, &venue_name, &e));
+                            Err(e)
+                        }
+                    };
+                }
+                None => {}
+            }
+
+            match self.race_slots(params, matching_slots, accounts, &dead, logger.clone()).await {
+                Ok(booked) => {
+                    self.notifier.emit(Event::booked(params, &venue_name, &booked));
+                    return Ok(Some(booked));
+                }
+                // Neither a fatal error nor an unusable card goes away by polling again
+                Err(e) if !breaker::is_fatal(&e) && !e.is::<PaymentMethodUnusable>() && Instant::now() < deadline => {
+                    logger.log(&format!("🔁 {}; polling for fresh availability", e));
+                    // Give the next round a moment, longer each time
+                    sleep(params.retry.delay(round)).await;
+                    round += 1;
+                }
+                Err(e) => {
+                    self.notifier.emit(Event::failed(params, &venue_name, &e));
+                    return Err(e);
+                }
+            }
         }
+    }
 
-        if dry_run {
-            logger.log("🏃 Dry run mode - skipping actual booking");
-            return Ok(());
+    /// Race concurrent booking threads for already-found slots, trying them in order,
+    /// and report the outcome. Threads are spread round-robin across `accounts`
+    /// (this client if empty).
+    pub async fn book_slots(
+        &self,
+        params: &BookingParams,
+        venue_name: &str,
+        matching_slots: Vec<Slot>,
+        accounts: &[ResyClient],
+        logger: LoggerHandle,
+    ) -> Result<BookedSlot> {
+        match self.race_slots(params, matching_slots, accounts, &Arc::default(), logger).await {
+            Ok(booked) => {
+                self.notifier.emit(Event::booked(params, venue_name, &booked));
+                Ok(booked)
+            }
+            Err(e) => {
+                self.notifier.emit(Event::failed(params, venue_name, &e));
+                Err(e)
+            }
         }
+    }
 
-        // Lock-free coordination using atomics
+    /// Race booking threads for `matching_slots`. A slot one thread finds taken (or
+    /// over the policy limits) is added to `dead` and skipped by all of them.
+    async fn race_slots(
+        &self,
+        params: &BookingParams,
+        matching_slots: Vec<Slot>,
+        accounts: &[ResyClient],
+        dead: &DeadSlots,
+        logger: LoggerHandle,
+    ) -> Result<BookedSlot> {
+        // Lock-free coordination using atomics; the winner records its booking
         let success = Arc::new(AtomicBool::new(false));
+        let winner: Arc<std::sync::Mutex<Option<BookedSlot>>> = Arc::new(std::sync::Mutex::new(None));
+        // Accounts whose payment method can't be used, by profile
+        let unpayable: Arc<std::sync::Mutex<HashMap<String, String>>> = Arc::default();
         let attempts = Arc::new(AtomicUsize::new(0));
         
+        let accounts = if accounts.is_empty() {
+            std::slice::from_ref(self)
+        } else {
+            accounts
+        };
+        // Every account gets at least one thread
+        let num_threads = params.num_threads.max(accounts.len());
+        let num_retries = params.num_retries;
+        let retry_policy = params.retry.clone();
+        let party_size = params.party_size;
+        let payment_method_id = params.payment_method_id;
+        let policy_limits = params.policy_limits;
+
         logger.log(&format!("🚀 Launching {} concurrent booking threads...", num_threads));
+        logger.log(&format!("   Retries: {} per slot, backoff {}", num_retries, params.retry.describe()));
+        if accounts.len() > 1 {
+            let names: Vec<_> = accounts.iter().map(|a| a.profile()).collect();
+            logger.log(&format!("   Accounts: {}", names.join(", ")));
+        }
         
-        let mut handles = Vec::new();
+        // Dropping the race (e.g. aborting the job running it) aborts every thread;
+        // the guard is declared last so its flag is raised before the threads go
+        let mut threads = JoinSet::new();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let _cancel_guard = CancelOnDrop(Arc::clone(&cancelled));
 
         // Spawn multiple concurrent tasks for booking attempts
         for thread_id in 0..num_threads {
-            let slot = matching_slots[0].clone(); // Try the first matching slot
-            let day = day.to_string();
+            let slots = matching_slots.clone(); // Try slots in order, starting with the first
+            let day = params.day.clone();
             let success = Arc::clone(&success);
+            let winner = Arc::clone(&winner);
             let attempts = Arc::clone(&attempts);
             let thread_logger = logger.clone();
+            let retry_policy = retry_policy.clone();
+            let dead = Arc::clone(dead);
+            let cancelled = Arc::clone(&cancelled);
+            let unpayable = Arc::clone(&unpayable);
             
-            // Clone client data for each thread
-            let api_key = self.api_key.clone();
-            let auth_token = self.auth_token.clone();
-
-            let handle = tokio::spawn(async move {
-                // Each thread creates its own client for true concurrency
-                let client = match ResyClient::new(api_key, auth_token) {
-                    Ok(c) => c,
-                    Err(e) => {
-                        thread_logger.log(&format!("   Thread {}: Failed to create client: {}", thread_id, e));
-                        return;
-                    }
-                };
+            // Spread threads across accounts; each thread gets its own
+            // connection pool for true concurrency
+            let client = match accounts[thread_id % accounts.len()].fork() {
+                Ok(c) => c,
+                Err(e) => {
+                    logger.log(&format!("   Thread {}: Failed to create client: {}", thread_id, e));
+                    continue;
+                }
+            };
+
+            threads.spawn(async move {
+                let mut slot_index = 0;
+                // Retries (and the retry time limit) count per slot
+                let mut retry_slot = 0;
+                let mut retry = 0;
+                let mut started = Instant::now();
 
-                for retry in 0..num_retries {
+                while slot_index < slots.len() {
                     // Check if another thread already succeeded
                     if success.load(Ordering::Relaxed) {
                         return;
                     }
+                    if retry_slot != slot_index {
+                        retry_slot = slot_index;
+                        retry = 0;
+                        started = Instant::now();
+                    }
 
+                    let slot = &slots[slot_index];
+                    if dead.lock().unwrap().contains(&slot.config.token) {
+                        slot_index += 1;
+                        continue;
+                    }
+                    if cancelled.load(Ordering::SeqCst) {
+                        return;
+                    }
                     attempts.fetch_add(1, Ordering::Relaxed);
                     
-                    match client.try_book_slot(&slot, &day, party_size).await {
-                        Ok(_) => {
+                    match client.try_book_slot(slot, &day, party_size, payment_method_id, &policy_limits).await {
+                        Ok(confirmation) => {
                             // Mark success atomically
                             if !success.swap(true, Ordering::SeqCst) {
-                                thread_logger.log(&format!("   ✅ Thread {} succeeded on attempt {}", thread_id, retry + 1));
+                                thread_logger.log(&format!("   ✅ Thread {} ({}) succeeded on attempt {}",
+                                    thread_id, client.profile(), retry + 1));
+                                *winner.lock().unwrap() = Some(BookedSlot {
+                                    slot: slot.clone(),
+                                    profile: client.profile().to_string(),
+                                    confirmation,
+                                });
+                            }
+                            return;
+                        }
+                        // A fatal error only stops the threads booking with that account
+                        Err(e) if e.downcast_ref::<BudgetExhausted>().is_some() || breaker::is_fatal(&e) => {
+                            thread_logger.log(&format!("   🛑 Thread {} stopping: {}", thread_id, e));
+                            return;
+                        }
+                        Err(e) if e.downcast_ref::<SlotTaken>().is_some() => {
+                            if dead.lock().unwrap().insert(slot.config.token.clone()) {
+                                thread_logger.log(&format!("   💀 Thread {}: {} ({}) is gone, moving on: {}",
+                                    thread_id, slot.date.start, slot.config.slot_type, e));
                             }
+                            slot_index += 1;
+                        }
+                        Err(e) if e.is::<PaymentMethodUnusable>() => {
+                            thread_logger.log(&format!("   🛑 Thread {} ({}) stopping: {}", thread_id, client.profile(), e));
+                            unpayable.lock().unwrap().insert(client.profile().to_string(), e.to_string());
                             return;
                         }
+                        Err(e) if e.downcast_ref::<PolicyViolation>().is_some() => {
+                            // The policy won't change on retry; drop the slot for every thread
+                            if dead.lock().unwrap().insert(slot.config.token.clone()) {
+                                thread_logger.log(&format!("   ⛔ Thread {} skipping {}: {}",
+                                    thread_id, slot.date.start, e));
+                            }
+                            slot_index += 1;
+                        }
+                        Err(e) if !retry_policy.is_retryable(&e) => {
+                            thread_logger.log(&format!("   ⚠️  Thread {} giving up on {}: {}",
+                                thread_id, slot.date.start, e));
+                            slot_index += 1;
+                        }
                         Err(e) => {
-                            if retry == 0 || retry == num_retries - 1 {
-                                thread_logger.log(&format!("   ⚠️  Thread {} attempt {}/{}: {}", 
-                                    thread_id, retry + 1, num_retries, e));
+                            let last = retry >= num_retries || retry_policy.out_of_time(started);
+                            if retry == 0 || last {
+                                thread_logger.log(&format!("   ⚠️  Thread {} attempt {}/{} on {}: {}",
+                                    thread_id, retry + 1, num_retries + 1, slot.date.start, e));
                             }
-                            // Small delay before retry (exponential backoff)
-                            if retry < num_retries - 1 {
-                                sleep(Duration::from_millis(50 * (retry as u64 + 1))).await;
+                            if last {
+                                slot_index += 1;
+                                continue;
                             }
+                            sleep(retry_policy.delay(retry as u32)).await;
+                            retry += 1;
                         }
                     }
                 }
             });
-
-            handles.push(handle);
         }
 
         // Wait for all threads to complete
-        for handle in handles {
-            let _ = handle.await;
-        }
+        while threads.join_next().await.is_some() {}
 
         let total_attempts = attempts.load(Ordering::Relaxed);
+        logger.log(&format!("   API requests: {} ({} rate-limited)", self.limiter.requests(), self.limiter.throttled()));
         
-        if success.load(Ordering::Relaxed) {
+        let booked = winner.lock().unwrap().take();
+        let unpayable = std::mem::take(&mut *unpayable.lock().unwrap());
+        let unpayable_everywhere = (unpayable.len() == accounts.len())
+            .then(|| unpayable.into_values().next())
+            .flatten();
+        if let Some(booked) = booked {
             logger.log("");
             logger.log("🎉 Successfully booked reservation!");
+            logger.log(&format!("   Slot: {} ({})", booked.slot.date.start, booked.slot.config.slot_type));
+            if let Some(id) = booked.confirmation.reservation_id {
+                logger.log(&format!("   Reservation ID: {}", id));
+            }
             logger.log(&format!("   Total attempts: {}", total_attempts));
-            Ok(())
+            Ok(booked)
+        } else if let Err(fatal) = self.live_account(accounts) {
+            Err(fatal)
+        } else if let Some(reason) = unpayable_everywhere {
+            Err(PaymentMethodUnusable(reason).into())
+        } else if matching_slots.iter().all(|slot| dead.lock().unwrap().contains(&slot.config.token)) {
+            Err(AllSlotsTaken(matching_slots.len()).into())
         } else {
-            anyhow::bail!(
+            Err(anyhow::anyhow!(
                 "❌ Failed to book after {} total attempts across {} threads",
                 total_attempts,
                 num_threads
-            )
+            ))
         }
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slot_taken_phrases_are_recognized() {
+        for phrase in SLOT_TAKEN_PHRASES {
+            assert!(is_slot_taken(StatusCode::BAD_REQUEST, phrase), "{}", phrase);
+        }
+        let cases = [
+            (400, "{\"message\": \"Sorry, this slot is no longer available.\"}"),
+            (404, "Reservation no longer available"),
+            (409, "The slot has already been booked"),
+            (412, "TABLE IS NO LONGER AVAILABLE"),
+        ];
+        for (status, body) in cases {
+            assert!(is_slot_taken(StatusCode::from_u16(status).unwrap(), body), "{} {}", status, body);
+        }
+    }
+
+    #[test]
+    fn other_rejections_are_not_slot_taken() {
+        let cases = [
+            (400, ""),
+            (404, "Not found"),
+            (412, "Payment method not available"),
+            (403, "Invalid auth token"),
+            (429, "Too many requests"),
+            (500, "Slot is no longer available"),
+            (503, "Reservation is not available"),
+        ];
+        for (status, body) in cases {
+            assert!(!is_slot_taken(StatusCode::from_u16(status).unwrap(), body), "{} {}", status, body);
+        }
+    }
+}
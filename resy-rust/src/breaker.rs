@@ -0,0 +1,162 @@
+use anyhow::Result;
+use reqwest::StatusCode;
+use std::fmt;
+use std::sync::OnceLock;
+
+/// Process exit code for a run stopped by a fatal auth error
+pub const FATAL_EXIT_CODE: u8 = 3;
+
+/// Why Resy will keep refusing every request, so retrying is pointless
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FatalReason {
+    /// The auth token is expired or revoked and couldn't be refreshed
+    Unauthorized,
+    InvalidApiKey,
+    AccountLocked,
+    /// A 403 that names auth as the reason
+    Forbidden,
+}
+
+impl FatalReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FatalReason::Unauthorized => "auth token rejected",
+            FatalReason::InvalidApiKey => "API key rejected",
+            FatalReason::AccountLocked => "account locked",
+            FatalReason::Forbidden => "access forbidden",
+        }
+    }
+
+    /// Classify a rejected response; None for responses worth retrying, such as
+    /// a firewall's 403 page or a slot that has just been taken
+    pub fn classify(status: StatusCode, body: &str) -> Option<Self> {
+        let body = body.to_lowercase();
+        let mentions = |phrases: &[&str]| phrases.iter().any(|phrase| body.contains(phrase));
+
+        if !matches!(status.as_u16(), 401 | 403 | 419) {
+            return None;
+        }
+        if mentions(INVALID_API_KEY) {
+            return Some(FatalReason::InvalidApiKey);
+        }
+        if mentions(ACCOUNT_LOCKED) {
+            return Some(FatalReason::AccountLocked);
+        }
+        match status.as_u16() {
+            401 | 419 => Some(FatalReason::Unauthorized),
+            _ if mentions(AUTH_REQUIRED) => Some(FatalReason::Forbidden),
+            _ => None,
+        }
+    }
+}
+
+/// Messages for a rejected API key
+const INVALID_API_KEY: &[&str] = &["invalid api key", "api key is invalid", "missing api key", "invalid_api_key"];
+
+/// Messages for an account that can't be used at all
+const ACCOUNT_LOCKED: &[&str] = &[
+    "account is locked",
+    "account has been locked",
+    "account is suspended",
+    "account has been suspended",
+    "account is disabled",
+    "account has been disabled",
+];
+
+/// Messages for a 403 that is about the credentials, not the request
+const AUTH_REQUIRED: &[&str] = &[
+    "invalid auth token",
+    "auth token is invalid",
+    "auth token has expired",
+    "expired auth token",
+    "authentication required",
+    "not authenticated",
+    "please log in",
+];
+
+/// An error that tripped the circuit breaker
+#[derive(Debug, Clone)]
+pub struct FatalError {
+    pub reason: FatalReason,
+    pub profile: String,
+    pub status: StatusCode,
+}
+
+impl fmt::Display for FatalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Fatal: {} for profile '{}' ({}); stopped every request with it", self.reason.as_str(), self.profile, self.status)
+    }
+}
+
+impl std::error::Error for FatalError {}
+
+/// Shared by every client of one account in a run. The first fatal error trips
+/// it, and from then on every request with that account fails immediately.
+#[derive(Debug, Default)]
+pub struct CircuitBreaker {
+    tripped: OnceLock<FatalError>,
+}
+
+impl CircuitBreaker {
+    /// Trip the breaker; returns false if it had already tripped
+    pub fn trip(&self, error: FatalError) -> bool {
+        self.tripped.set(error).is_ok()
+    }
+
+    pub fn tripped(&self) -> Option<&FatalError> {
+        self.tripped.get()
+    }
+
+    pub fn check(&self) -> Result<()> {
+        match self.tripped() {
+            Some(error) => Err(error.clone().into()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Whether `error` means the run should stop rather than retry
+pub fn is_fatal(error: &anyhow::Error) -> bool {
+    error.downcast_ref::<FatalError>().is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_recognizes_fatal_responses() {
+        let cases = [
+            (401, "", Some(FatalReason::Unauthorized)),
+            (419, "Session expired", Some(FatalReason::Unauthorized)),
+            (401, "{\"message\": \"Invalid API key\"}", Some(FatalReason::InvalidApiKey)),
+            (403, "invalid_api_key", Some(FatalReason::InvalidApiKey)),
+            (403, "Missing API key", Some(FatalReason::InvalidApiKey)),
+            (403, "Your account has been suspended", Some(FatalReason::AccountLocked)),
+            (401, "Account is locked", Some(FatalReason::AccountLocked)),
+            (403, "Invalid auth token", Some(FatalReason::Forbidden)),
+            (403, "Auth token has expired", Some(FatalReason::Forbidden)),
+            (403, "Please log in to continue", Some(FatalReason::Forbidden)),
+        ];
+        for (status, body, expected) in cases {
+            assert_eq!(FatalReason::classify(StatusCode::from_u16(status).unwrap(), body), expected, "{} {}", status, body);
+        }
+    }
+
+    #[test]
+    fn classify_ignores_retryable_responses() {
+        let cases = [
+            (403, ""),
+            (403, "<html>Access denied by firewall</html>"),
+            (403, "Slot is no longer available"),
+            (400, "Invalid API key"),
+            (404, "Account is locked"),
+            (429, "Too many requests"),
+            (500, "Authentication required"),
+            (412, "Payment method not available"),
+        ];
+        for (status, body) in cases {
+            assert_eq!(FatalReason::classify(StatusCode::from_u16(status).unwrap(), body), None, "{} {}", status, body);
+        }
+    }
+}
@@ -0,0 +1,365 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::notify::EventKind;
+use crate::retry::RetryPolicy;
+
+/// Root directory for everything resy-rust keeps on disk (~/.resy-rust)
+pub fn app_dir() -> PathBuf {
+    let home = env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home).join(".resy-rust")
+}
+
+/// Writes a file holding secrets so it is never readable by other users, even
+/// briefly: new files are created 0600 and existing ones are narrowed before
+/// the contents go in.
+pub fn write_private(path: &Path, contents: &str) -> Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path).context(format!("Failed to open {}", path.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(0o600))
+            .context(format!("Failed to set permissions on {}", path.display()))?;
+    }
+    file.write_all(contents.as_bytes()).context(format!("Failed to write {}", path.display()))
+}
+
+pub fn default_config_path() -> PathBuf {
+    app_dir().join("config.toml")
+}
+
+/// Where a profile's credentials are loaded from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CredentialSource {
+    /// `api_key` / `auth_token` in this file
+    #[default]
+    Config,
+    /// RESY_API_KEY / RESY_AUTH_TOKEN environment variables
+    Env,
+    /// stdout of `command`
+    Command,
+    /// First line of stdin
+    Stdin,
+    /// Passphrase-encrypted file at `encrypted_file`
+    Encrypted,
+}
+
+/// A single Resy account
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Profile {
+    #[serde(default)]
+    pub source: CredentialSource,
+
+    /// Falls back to the top-level `api_key` (or RESY_API_KEY) when omitted
+    #[serde(default)]
+    pub api_key: Option<String>,
+    #[serde(default)]
+    pub auth_token: Option<String>,
+
+    /// Shell command that prints the auth token (source = "command")
+    #[serde(default)]
+    pub command: Option<String>,
+
+    /// Account email, used by `login` and to re-authenticate when the token is rejected
+    #[serde(default)]
+    pub email: Option<String>,
+
+    /// Password for re-authentication (prefer `password_command` or RESY_PASSWORD)
+    #[serde(default)]
+    pub password: Option<String>,
+
+    /// Shell command that prints the account password
+    #[serde(default)]
+    pub password_command: Option<String>,
+
+    /// Encrypted credentials file (source = "encrypted"),
+    /// default ~/.resy-rust/credentials/<profile>.enc
+    #[serde(default)]
+    pub encrypted_file: Option<PathBuf>,
+}
+
+fn default_webhook_retries() -> u32 {
+    3
+}
+
+/// An outbound webhook that receives booking events as JSON
+#[derive(Debug, Clone, Deserialize)]
+pub struct WebhookConfig {
+    pub url: String,
+
+    /// Events to send (default: all)
+    #[serde(default)]
+    pub events: Vec<EventKind>,
+
+    /// Payload template with {{placeholders}} (default: the full event as JSON)
+    #[serde(default)]
+    pub template: Option<String>,
+
+    /// Extra request headers, e.g. Authorization
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+
+    /// Retries after the first attempt for network errors, 429s and 5xx
+    #[serde(default = "default_webhook_retries")]
+    pub max_retries: u32,
+}
+
+/// How to secure the SMTP connection
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpSecurity {
+    /// Upgrade a plain connection with STARTTLS (default port 587)
+    #[default]
+    Starttls,
+    /// Implicit TLS (default port 465)
+    Tls,
+    /// Plain SMTP, e.g. for a local mail catcher (default port 25)
+    None,
+}
+
+/// SMTP server that receives booking summaries by email
+#[derive(Debug, Clone, Deserialize)]
+pub struct SmtpConfig {
+    pub host: String,
+    #[serde(default)]
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub security: SmtpSecurity,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Shell command that prints the SMTP password
+    #[serde(default)]
+    pub password_command: Option<String>,
+    pub from: String,
+    pub to: Vec<String>,
+    /// Events to email (default: all)
+    #[serde(default)]
+    pub events: Vec<EventKind>,
+}
+
+fn default_requests_per_sec() -> f64 {
+    10.0
+}
+
+fn default_burst() -> u32 {
+    10
+}
+
+/// Client-side limits on requests to the Resy API, shared by every task in a run
+#[derive(Debug, Clone, Deserialize)]
+pub struct RateLimitConfig {
+    /// Sustained request rate; halved on each 429 and slowly restored
+    #[serde(default = "default_requests_per_sec")]
+    pub requests_per_sec: f64,
+    /// Requests that can go out back to back before the rate applies
+    #[serde(default = "default_burst")]
+    pub burst: u32,
+    /// Hard cap on requests per run; unlimited when unset
+    #[serde(default)]
+    pub request_budget: Option<usize>,
+}
+
+impl RateLimitConfig {
+    pub fn validate(&self) -> Result<()> {
+        if !(self.requests_per_sec.is_finite() && self.requests_per_sec > 0.0) {
+            anyhow::bail!("rate_limit.requests_per_sec must be greater than 0");
+        }
+        if self.request_budget == Some(0) {
+            anyhow::bail!("rate_limit.request_budget must be at least 1");
+        }
+        Ok(())
+    }
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            requests_per_sec: default_requests_per_sec(),
+            burst: default_burst(),
+            request_budget: None,
+        }
+    }
+}
+
+fn default_api_base() -> String {
+    "https://api.resy.com".to_string()
+}
+
+fn default_pin_dns() -> bool {
+    true
+}
+
+/// How clients connect to the Resy API
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct NetworkConfig {
+    /// Base URL every API request goes to
+    #[serde(default = "default_api_base")]
+    pub api_base: String,
+    /// Negotiate HTTP/2 instead of forcing HTTP/1.1 (compare them with `resy-rust latency`)
+    #[serde(default)]
+    pub http2: bool,
+    /// Resolve the API host just before a release and reuse the addresses for the run
+    #[serde(default = "default_pin_dns")]
+    pub pin_dns: bool,
+    /// When pinning, connect to every resolved address and prefer the fastest
+    #[serde(default)]
+    pub race_connections: bool,
+}
+
+impl NetworkConfig {
+    /// `api_base` without a trailing slash, ready to append paths to
+    pub fn base(&self) -> &str {
+        self.api_base.trim_end_matches('/')
+    }
+
+    /// `api_base` parsed, checked to be an http(s) URL with a host
+    pub fn url(&self) -> Result<reqwest::Url> {
+        let url = reqwest::Url::parse(&self.api_base)
+            .context(format!("network.api_base '{}' is not a URL", self.api_base))?;
+        if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none() {
+            anyhow::bail!("network.api_base must be an http or https URL with a host");
+        }
+        Ok(url)
+    }
+
+    /// Host and port of `api_base`
+    pub fn host_port(&self) -> Result<(String, u16)> {
+        let url = self.url()?;
+        // IPv6 hosts come bracketed, which resolvers don't accept
+        let host = url.host_str().unwrap_or_default().trim_start_matches('[').trim_end_matches(']').to_string();
+        let port = url.port_or_known_default().context("network.api_base has no port")?;
+        Ok((host, port))
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        self.host_port().map(|_| ())
+    }
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            api_base: default_api_base(),
+            http2: false,
+            pin_dns: default_pin_dns(),
+            race_connections: false,
+        }
+    }
+}
+
+/// Contents of ~/.resy-rust/config.toml
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Config {
+    /// Profile used when no --profile flag is given
+    #[serde(default)]
+    pub default_profile: Option<String>,
+
+    /// API key shared by every profile that doesn't set its own
+    #[serde(default)]
+    pub api_key: Option<String>,
+
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+
+    #[serde(default)]
+    pub webhooks: Vec<WebhookConfig>,
+
+    #[serde(default)]
+    pub smtp: Option<SmtpConfig>,
+
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+
+    /// Backoff for failed polls and booking attempts
+    #[serde(default)]
+    pub retry: RetryPolicy,
+
+    #[serde(default)]
+    pub network: NetworkConfig,
+}
+
+impl Config {
+    /// Load the config file, returning an empty config if it doesn't exist
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = fs::read_to_string(path)
+            .context(format!("Failed to read config file: {}", path.display()))?;
+        let config: Self = toml::from_str(&contents)
+            .context(format!("Failed to parse config file: {}", path.display()))?;
+        config.rate_limit.validate()?;
+        config.retry.validate()?;
+        config.network.validate()?;
+        Ok(config)
+    }
+
+    /// Set keys of `[profiles.<name>]` in the config file, preserving the rest
+    /// of the file. Also makes the profile the default if none is set.
+    pub fn update_profile(path: &Path, name: &str, values: &[(&str, &str)]) -> Result<()> {
+        let contents = if path.exists() {
+            fs::read_to_string(path)
+                .context(format!("Failed to read config file: {}", path.display()))?
+        } else {
+            String::new()
+        };
+        let mut doc: toml_edit::DocumentMut = contents
+            .parse()
+            .context(format!("Failed to parse config file: {}", path.display()))?;
+
+        if !doc.contains_key("default_profile") {
+            doc["default_profile"] = toml_edit::value(name);
+        }
+
+        let profiles = doc
+            .entry("profiles")
+            .or_insert_with(|| {
+                let mut table = toml_edit::Table::new();
+                table.set_implicit(true);
+                toml_edit::Item::Table(table)
+            })
+            .as_table_mut()
+            .context("`profiles` in the config file is not a table")?;
+        let profile = profiles
+            .entry(name)
+            .or_insert_with(toml_edit::table)
+            .as_table_mut()
+            .context(format!("`profiles.{}` in the config file is not a table", name))?;
+        for (key, value) in values {
+            profile[*key] = toml_edit::value(*value);
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        write_private(path, &doc.to_string())
+            .context(format!("Failed to write config file: {}", path.display()))
+    }
+
+    pub fn profile(&self, name: &str) -> Result<&Profile> {
+        self.profiles.get(name).with_context(|| {
+            let known: Vec<_> = self.profiles.keys().map(String::as_str).collect();
+            if known.is_empty() {
+                format!("Profile '{}' not found: no profiles are configured", name)
+            } else {
+                format!("Profile '{}' not found (known profiles: {})", name, known.join(", "))
+            }
+        })
+    }
+}
@@ -0,0 +1,270 @@
+use anyhow::{Context, Result};
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+
+use crate::config::{self, Config};
+use crate::daemon::{self, DaemonState};
+use crate::schedule::{self, Job, JobSpec, JobStatus};
+use crate::LoggerHandle;
+
+/// Environment variable that sets the control API token
+pub const TOKEN_ENV: &str = "RESY_DAEMON_TOKEN";
+
+const DEFAULT_LOG_LINES: usize = 100;
+
+/// Shortest control API token accepted
+const MIN_TOKEN_LEN: usize = 32;
+
+/// The control API token: $RESY_DAEMON_TOKEN, else ~/.resy-rust/daemon.token,
+/// which is created with a random token on first use
+pub fn load_or_create_token() -> Result<String> {
+    if let Ok(token) = std::env::var(TOKEN_ENV) {
+        return check_token(token.trim(), TOKEN_ENV);
+    }
+
+    let path = token_path();
+    if path.exists() {
+        let token = fs::read_to_string(&path).context(format!("Failed to read {}", path.display()))?;
+        return check_token(token.trim(), &path.display().to_string());
+    }
+
+    let token: String = std::iter::repeat_with(fastrand::alphanumeric).take(40).collect();
+    fs::create_dir_all(config::app_dir())?;
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&path).context(format!("Failed to create {}", path.display()))?;
+    file.write_all(token.as_bytes()).context(format!("Failed to write {}", path.display()))?;
+    Ok(token)
+}
+
+/// Refuse empty or guessable tokens, which would leave the API open
+fn check_token(token: &str, source: &str) -> Result<String> {
+    if token.len() < MIN_TOKEN_LEN {
+        anyhow::bail!("The control API token from {} must be at least {} characters", source, MIN_TOKEN_LEN);
+    }
+    Ok(token.to_string())
+}
+
+pub fn token_path() -> PathBuf {
+    config::app_dir().join("daemon.token")
+}
+
+/// Bind the API listener, refusing anything but a loopback address
+pub async fn bind(addr: SocketAddr) -> Result<TcpListener> {
+    if !addr.ip().is_loopback() {
+        anyhow::bail!("The control API only listens on loopback addresses, not {}", addr);
+    }
+    TcpListener::bind(addr)
+        .await
+        .context(format!("Failed to listen on {}", addr))
+}
+
+#[derive(Clone)]
+struct ApiState {
+    daemon: Arc<DaemonState>,
+    logger: LoggerHandle,
+}
+
+pub async fn serve(listener: TcpListener, token: String, daemon: Arc<DaemonState>, logger: LoggerHandle) {
+    let state = ApiState {
+        daemon,
+        logger: logger.clone(),
+    };
+    let app = Router::new()
+        .route("/jobs", get(list_jobs).post(submit_job))
+        .route("/jobs/{id}", get(get_job))
+        .route("/jobs/{id}/pause", post(pause_job))
+        .route("/jobs/{id}/resume", post(resume_job))
+        .route("/jobs/{id}/cancel", post(cancel_job))
+        .route("/jobs/{id}/logs", get(job_logs))
+        .route("/logs", get(daemon_logs))
+        .layer(middleware::from_fn_with_state(Arc::new(token), require_token))
+        .with_state(state);
+
+    if let Err(e) = axum::serve(listener, app).await {
+        logger.log(&format!("⚠️  Control API stopped: {}", e));
+    }
+}
+
+/// Reject requests without `Authorization: Bearer <token>`
+async fn require_token(State(token): State<Arc<String>>, request: Request, next: Next) -> Response {
+    let presented = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .unwrap_or_default();
+
+    if !constant_time_eq(presented.as_bytes(), token.as_bytes()) {
+        return ApiError(StatusCode::UNAUTHORIZED, "Missing or invalid bearer token".to_string()).into_response();
+    }
+    next.run(request).await
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(serde_json::json!({ "error": self.1 }))).into_response()
+    }
+}
+
+type ApiResult<T> = std::result::Result<T, ApiError>;
+
+fn internal(e: anyhow::Error) -> ApiError {
+    ApiError(StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", e))
+}
+
+/// Progress of a job the daemon is running right now
+#[derive(Serialize)]
+struct LiveStatus {
+    started_at: DateTime<Utc>,
+    polls: usize,
+    booking_attempts: usize,
+    log_file: PathBuf,
+}
+
+#[derive(Serialize)]
+struct JobView {
+    #[serde(flatten)]
+    job: Job,
+    live: Option<LiveStatus>,
+}
+
+impl ApiState {
+    fn find(&self, id: &str) -> ApiResult<Job> {
+        schedule::validate_id(id).map_err(|e| ApiError(StatusCode::BAD_REQUEST, e.to_string()))?;
+        if !self.daemon.store.exists(id) {
+            return Err(ApiError(StatusCode::NOT_FOUND, format!("No job '{}'", id)));
+        }
+        self.daemon.store.get(id).map_err(internal)
+    }
+
+    fn view(&self, job: Job) -> JobView {
+        let running = self.daemon.running.lock().unwrap();
+        let live = running.get(&job.id).filter(|running| !running.is_finished()).map(|running| LiveStatus {
+            started_at: running.started_at,
+            polls: running.stats.polls.load(Ordering::Relaxed),
+            booking_attempts: running.stats.booking_attempts.load(Ordering::Relaxed),
+            log_file: running.log_path.clone(),
+        });
+        JobView { job, live }
+    }
+
+    fn save(&self, job: &Job) -> ApiResult<()> {
+        self.daemon.store.save(job).map(|_| ()).map_err(internal)
+    }
+}
+
+async fn list_jobs(State(state): State<ApiState>) -> ApiResult<Json<Vec<JobView>>> {
+    let jobs = state.daemon.store.list().map_err(internal)?;
+    Ok(Json(jobs.into_iter().map(|job| state.view(job)).collect()))
+}
+
+async fn get_job(State(state): State<ApiState>, Path(id): Path<String>) -> ApiResult<Json<JobView>> {
+    let job = state.find(&id)?;
+    Ok(Json(state.view(job)))
+}
+
+async fn submit_job(State(state): State<ApiState>, Json(spec): Json<JobSpec>) -> ApiResult<(StatusCode, Json<JobView>)> {
+    let config = Config::load(&state.daemon.config_path).map_err(internal)?;
+    let logger = state.logger.clone();
+    let warn = move |warning: &str| logger.log(&format!("⚠️  {}", warning));
+    let job = schedule::create_job(spec, &config, &state.daemon.credentials, &warn)
+        .await
+        .map_err(|e| ApiError(StatusCode::BAD_REQUEST, format!("{:#}", e)))?;
+
+    state.save(&job)?;
+    state.logger.log(&format!("📅 API: scheduled {} for {} on {} (release {})",
+        job.id, job.venue_label(), job.dates.join(", "), job.release_local()));
+    state.daemon.wake.notify_one();
+    Ok((StatusCode::CREATED, Json(state.view(job))))
+}
+
+async fn pause_job(State(state): State<ApiState>, Path(id): Path<String>) -> ApiResult<Json<JobView>> {
+    let mut job = state.find(&id)?;
+    if !matches!(job.status, JobStatus::Pending | JobStatus::Running) {
+        return Err(ApiError(StatusCode::CONFLICT, format!("Job '{}' is {}", id, job.status.as_str())));
+    }
+
+    if state.daemon.stop(&id) {
+        state.logger.log(&format!("⏸️  API: stopped running job {}", id));
+    }
+    job.status = JobStatus::Paused;
+    state.save(&job)?;
+    state.logger.log(&format!("⏸️  API: paused {}", id));
+    Ok(Json(state.view(job)))
+}
+
+async fn resume_job(State(state): State<ApiState>, Path(id): Path<String>) -> ApiResult<Json<JobView>> {
+    let mut job = state.find(&id)?;
+    if job.status != JobStatus::Paused {
+        return Err(ApiError(StatusCode::CONFLICT, format!("Job '{}' is {}, not paused", id, job.status.as_str())));
+    }
+
+    job.status = JobStatus::Pending;
+    state.save(&job)?;
+    state.logger.log(&format!("▶️  API: resumed {}", id));
+    state.daemon.wake.notify_one();
+    Ok(Json(state.view(job)))
+}
+
+async fn cancel_job(State(state): State<ApiState>, Path(id): Path<String>) -> ApiResult<Json<JobView>> {
+    let mut job = state.find(&id)?;
+    if matches!(job.status, JobStatus::Booked | JobStatus::Failed | JobStatus::Cancelled) {
+        return Err(ApiError(StatusCode::CONFLICT, format!("Job '{}' is already {}", id, job.status.as_str())));
+    }
+
+    let log_file = state.daemon.running.lock().unwrap().get(&id).map(|running| running.log_path.clone());
+    state.daemon.stop(&id);
+    daemon::record(&state.daemon.store, &mut job, JobStatus::Cancelled, "Cancelled via the control API".to_string(), None, log_file);
+    state.logger.log(&format!("🛑 API: cancelled {}", id));
+    Ok(Json(state.view(job)))
+}
+
+#[derive(Deserialize)]
+struct LogQuery {
+    lines: Option<usize>,
+}
+
+fn tail(path: &std::path::Path, lines: usize) -> ApiResult<String> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| ApiError(StatusCode::NOT_FOUND, format!("Failed to read {}: {}", path.display(), e)))?;
+    let all: Vec<&str> = contents.lines().collect();
+    Ok(all[all.len().saturating_sub(lines)..].join("\n"))
+}
+
+async fn job_logs(State(state): State<ApiState>, Path(id): Path<String>, Query(query): Query<LogQuery>) -> ApiResult<String> {
+    let job = state.find(&id)?;
+    let running_log = state.daemon.running.lock().unwrap().get(&id).map(|running| running.log_path.clone());
+    let path = running_log
+        .or(job.outcome.and_then(|outcome| outcome.log_file))
+        .ok_or_else(|| ApiError(StatusCode::NOT_FOUND, format!("Job '{}' has no log yet", id)))?;
+    tail(&path, query.lines.unwrap_or(DEFAULT_LOG_LINES))
+}
+
+async fn daemon_logs(State(state): State<ApiState>, Query(query): Query<LogQuery>) -> ApiResult<String> {
+    tail(&state.daemon.log_path, query.lines.unwrap_or(DEFAULT_LOG_LINES))
+}
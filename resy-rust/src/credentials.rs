@@ -0,0 +1,393 @@
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::env;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::Mutex;
+
+use crate::config::{self, Config, CredentialSource, Profile};
+use crate::vault;
+
+/// Name used for credentials that come straight from RESY_API_KEY / RESY_AUTH_TOKEN
+pub const ENV_PROFILE: &str = "env";
+
+/// Environment variable checked for the encrypted store passphrase before prompting
+pub const PASSPHRASE_ENV: &str = "RESY_PASSPHRASE";
+
+/// Environment variables used for password login when a profile doesn't set them
+pub const EMAIL_ENV: &str = "RESY_EMAIL";
+pub const PASSWORD_ENV: &str = "RESY_PASSWORD";
+
+/// API credentials for one Resy account
+#[derive(Debug, Clone)]
+pub struct Credentials {
+    pub profile: String,
+    pub api_key: String,
+    pub auth_token: String,
+    /// Used to re-authenticate when the auth token is rejected
+    pub login: Option<PasswordLogin>,
+}
+
+/// Email and password for Resy's password auth endpoint
+#[derive(Clone)]
+pub struct PasswordLogin {
+    pub email: String,
+    pub password: String,
+}
+
+impl std::fmt::Debug for PasswordLogin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PasswordLogin")
+            .field("email", &self.email)
+            .field("password", &"<redacted>")
+            .finish()
+    }
+}
+
+/// Secrets produced by a provider. A missing api_key falls back to the shared key.
+#[derive(Debug, Clone)]
+pub struct Secret {
+    pub api_key: Option<String>,
+    pub auth_token: String,
+}
+
+/// A source of Resy credentials
+pub trait CredentialProvider {
+    fn load(&self) -> Result<Secret>;
+}
+
+/// RESY_API_KEY / RESY_AUTH_TOKEN environment variables (including .env)
+pub struct EnvProvider;
+
+impl CredentialProvider for EnvProvider {
+    fn load(&self) -> Result<Secret> {
+        let api_key = env::var("RESY_API_KEY")
+            .context("RESY_API_KEY not found in environment. Create a .env file with your credentials.")?;
+        let auth_token = env::var("RESY_AUTH_TOKEN")
+            .context("RESY_AUTH_TOKEN not found in environment. Create a .env file with your credentials.")?;
+
+        Ok(Secret {
+            api_key: Some(api_key),
+            auth_token,
+        })
+    }
+}
+
+/// Plaintext api_key / auth_token fields of a config profile
+pub struct ConfigProvider {
+    profile: String,
+    api_key: Option<String>,
+    auth_token: Option<String>,
+}
+
+impl CredentialProvider for ConfigProvider {
+    fn load(&self) -> Result<Secret> {
+        let auth_token = self
+            .auth_token
+            .clone()
+            .context(format!("Profile '{}' has no auth_token", self.profile))?;
+
+        Ok(Secret {
+            api_key: self.api_key.clone(),
+            auth_token,
+        })
+    }
+}
+
+/// Auth token printed to stdout by an external command (e.g. a password manager CLI)
+pub struct CommandProvider {
+    command: String,
+}
+
+impl CredentialProvider for CommandProvider {
+    fn load(&self) -> Result<Secret> {
+        Ok(Secret {
+            api_key: None,
+            auth_token: run_secret_command(&self.command)?,
+        })
+    }
+}
+
+/// Auth token read from the first line of stdin
+pub struct StdinProvider {
+    profile: String,
+}
+
+impl CredentialProvider for StdinProvider {
+    fn load(&self) -> Result<Secret> {
+        Ok(Secret {
+            api_key: None,
+            auth_token: read_token_from_stdin(&format!("Auth token for profile '{}': ", self.profile))?,
+        })
+    }
+}
+
+/// Passphrase-encrypted credentials file (see `resy-rust credentials encrypt`)
+pub struct EncryptedFileProvider {
+    path: PathBuf,
+}
+
+impl CredentialProvider for EncryptedFileProvider {
+    fn load(&self) -> Result<Secret> {
+        let passphrase = read_passphrase(&format!("Passphrase for {}: ", self.path.display()))?;
+        let contents = vault::decrypt(&passphrase, &self.path)?;
+
+        Ok(Secret {
+            api_key: contents.api_key,
+            auth_token: contents.auth_token,
+        })
+    }
+}
+
+/// Default location of a profile's encrypted credentials file
+pub fn default_encrypted_path(profile: &str) -> PathBuf {
+    config::app_dir().join("credentials").join(format!("{}.enc", profile))
+}
+
+/// Read an auth token from the first line of stdin, prompting if it's a terminal
+pub fn read_token_from_stdin(prompt: &str) -> Result<String> {
+    let stdin = io::stdin();
+    if stdin.is_terminal() {
+        eprint!("{}", prompt);
+        io::stderr().flush()?;
+    }
+
+    let mut line = String::new();
+    stdin.lock().read_line(&mut line).context("Failed to read auth token from stdin")?;
+    let auth_token = line.trim().to_string();
+    if auth_token.is_empty() {
+        anyhow::bail!("No auth token provided on stdin");
+    }
+    Ok(auth_token)
+}
+
+/// Read the vault passphrase from RESY_PASSPHRASE, or prompt for it without echo
+pub fn read_passphrase(prompt: &str) -> Result<String> {
+    if let Ok(passphrase) = env::var(PASSPHRASE_ENV) {
+        return Ok(passphrase);
+    }
+    rpassword::prompt_password(prompt).context("Failed to read passphrase")
+}
+
+/// Run a shell command and return its trimmed stdout
+pub fn run_secret_command(command: &str) -> Result<String> {
+    let output = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .output()
+        .context(format!("Failed to run credential command: {}", command))?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "Credential command exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    let value = String::from_utf8(output.stdout)
+        .context("Credential command printed invalid UTF-8")?
+        .trim()
+        .to_string();
+    if value.is_empty() {
+        anyhow::bail!("Credential command printed nothing: {}", command);
+    }
+    Ok(value)
+}
+
+/// Build the provider configured for a profile
+pub fn provider_for(name: &str, profile: &Profile) -> Result<Box<dyn CredentialProvider>> {
+    Ok(match profile.source {
+        CredentialSource::Env => Box::new(EnvProvider),
+        CredentialSource::Config => Box::new(ConfigProvider {
+            profile: name.to_string(),
+            api_key: profile.api_key.clone(),
+            auth_token: profile.auth_token.clone(),
+        }),
+        CredentialSource::Command => Box::new(CommandProvider {
+            command: profile
+                .command
+                .clone()
+                .context(format!("Profile '{}' uses source = \"command\" but sets no command", name))?,
+        }),
+        CredentialSource::Stdin => Box::new(StdinProvider {
+            profile: name.to_string(),
+        }),
+        CredentialSource::Encrypted => Box::new(EncryptedFileProvider {
+            path: profile
+                .encrypted_file
+                .clone()
+                .unwrap_or_else(|| default_encrypted_path(name)),
+        }),
+    })
+}
+
+/// Resolve credentials for the requested profiles.
+///
+/// With no `--profile` flag this uses the config's `default_profile`, falling
+/// back to the RESY_API_KEY / RESY_AUTH_TOKEN environment variables.
+pub fn resolve(config: &Config, profiles: &[String]) -> Result<Vec<Credentials>> {
+    if !profiles.is_empty() {
+        return profiles
+            .iter()
+            .map(|name| from_profile(config, name))
+            .collect();
+    }
+
+    if let Some(name) = &config.default_profile {
+        return Ok(vec![from_profile(config, name)?]);
+    }
+
+    let secret = EnvProvider.load()?;
+    Ok(vec![Credentials {
+        profile: ENV_PROFILE.to_string(),
+        api_key: secret.api_key.unwrap_or_default(),
+        auth_token: secret.auth_token,
+        login: env_login(),
+    }])
+}
+
+/// Whether loading a profile's credentials waits on a person: source = "stdin",
+/// or "encrypted" without RESY_PASSPHRASE set
+pub fn is_interactive(config: &Config, name: &str) -> bool {
+    match config.profile(name).map(|profile| profile.source) {
+        Ok(CredentialSource::Stdin) => true,
+        Ok(CredentialSource::Encrypted) => env::var(PASSPHRASE_ENV).is_err(),
+        _ => false,
+    }
+}
+
+/// Credentials loaded once per profile and reused, so stdin and passphrase
+/// sources prompt at most once. A profile that failed to load keeps failing
+/// with the same error instead of prompting again.
+#[derive(Default)]
+pub struct CredentialCache {
+    loaded: Mutex<HashMap<String, std::result::Result<Credentials, String>>>,
+    /// Never prompt: interactive profiles must be preloaded, and the others are
+    /// re-read on every resolve so refreshed tokens are picked up
+    unattended: bool,
+}
+
+impl CredentialCache {
+    /// A cache for the daemon, which has no one to answer prompts once it's running
+    pub fn unattended() -> Self {
+        Self {
+            unattended: true,
+            ..Self::default()
+        }
+    }
+
+    /// Load the interactive profiles among `profiles` now, while prompts can be answered
+    pub fn preload(&self, config: &Config, profiles: &[String]) -> Result<()> {
+        for name in profile_names(config, profiles).unwrap_or_default() {
+            if is_interactive(config, &name) && !self.loaded.lock().unwrap().contains_key(&name) {
+                let credentials = from_profile(config, &name)?;
+                self.loaded.lock().unwrap().insert(name, Ok(credentials));
+            }
+        }
+        Ok(())
+    }
+
+    /// Like `resolve`, but each profile is loaded at most once
+    pub fn resolve(&self, config: &Config, profiles: &[String]) -> Result<Vec<Credentials>> {
+        match profile_names(config, profiles) {
+            Some(names) => names.iter().map(|name| self.load(config, name)).collect(),
+            None => resolve(config, profiles),
+        }
+    }
+
+    fn load(&self, config: &Config, name: &str) -> Result<Credentials> {
+        let interactive = is_interactive(config, name);
+        if self.unattended && !interactive {
+            return from_profile(config, name);
+        }
+        if let Some(loaded) = self.loaded.lock().unwrap().get(name) {
+            return loaded.clone().map_err(|e| anyhow::anyhow!(e));
+        }
+        if self.unattended {
+            anyhow::bail!(
+                "Profile '{}' reads its credentials interactively, which the daemon can't do once it's running. \
+                 Restart the daemon to enter them, or set {} / use source = \"command\"",
+                name,
+                PASSPHRASE_ENV
+            );
+        }
+
+        let loaded = from_profile(config, name).map_err(|e| format!("{:#}", e));
+        self.loaded.lock().unwrap().insert(name.to_string(), loaded.clone());
+        loaded.map_err(|e| anyhow::anyhow!(e))
+    }
+}
+
+/// The profiles `resolve` would load, or None when it falls back to the environment
+fn profile_names(config: &Config, profiles: &[String]) -> Option<Vec<String>> {
+    if !profiles.is_empty() {
+        return Some(profiles.to_vec());
+    }
+    config.default_profile.clone().map(|name| vec![name])
+}
+
+fn env_login() -> Option<PasswordLogin> {
+    Some(PasswordLogin {
+        email: env::var(EMAIL_ENV).ok()?,
+        password: env::var(PASSWORD_ENV).ok()?,
+    })
+}
+
+/// Email/password for a profile: `password`, then `password_command`, then RESY_PASSWORD
+fn profile_login(name: &str, profile: &Profile) -> Result<Option<PasswordLogin>> {
+    let Some(email) = profile.email.clone() else {
+        return Ok(env_login());
+    };
+
+    let password = match (&profile.password, &profile.password_command) {
+        (Some(password), _) => password.clone(),
+        (None, Some(command)) => run_secret_command(command)
+            .context(format!("Failed to get password for profile '{}'", name))?,
+        (None, None) => match env::var(PASSWORD_ENV) {
+            Ok(password) => password,
+            Err(_) => return Ok(None),
+        },
+    };
+
+    Ok(Some(PasswordLogin { email, password }))
+}
+
+/// API key for a profile without loading its auth token (used by `login`)
+pub fn api_key_for(config: &Config, name: &str) -> Result<String> {
+    config
+        .profiles
+        .get(name)
+        .and_then(|profile| profile.api_key.clone())
+        .or_else(|| config.api_key.clone())
+        .or_else(|| env::var("RESY_API_KEY").ok())
+        .context(format!(
+            "No api_key for profile '{}': set api_key in the config or RESY_API_KEY",
+            name
+        ))
+}
+
+fn from_profile(config: &Config, name: &str) -> Result<Credentials> {
+    let profile = config.profile(name)?;
+    let secret = provider_for(name, profile)?
+        .load()
+        .context(format!("Failed to load credentials for profile '{}'", name))?;
+
+    let api_key = secret
+        .api_key
+        .or_else(|| profile.api_key.clone())
+        .or_else(|| config.api_key.clone())
+        .or_else(|| env::var("RESY_API_KEY").ok())
+        .context(format!(
+            "Profile '{}' has no api_key and RESY_API_KEY is not set",
+            name
+        ))?;
+
+    Ok(Credentials {
+        profile: name.to_string(),
+        api_key,
+        auth_token: secret.auth_token,
+        login: profile_login(name, profile)?,
+    })
+}
@@ -0,0 +1,410 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+
+use crate::api::{BookingParams, EngineStats, ResyClient};
+use crate::control;
+use crate::config::{Config, RateLimitConfig};
+use crate::credentials::CredentialCache;
+use crate::dns;
+use crate::notify::Notifier;
+use crate::poll;
+use crate::ratelimit::RateLimiter;
+use crate::schedule::{self, Job, JobLock, JobOutcome, JobStatus, JobStore, Strategy};
+use crate::watch::{self, WatchOptions, WatchTarget};
+use crate::{build_clients_sharing, check_token_expiry, get_default_log_path, Logger, LoggerHandle};
+
+/// How often the job store is re-read
+const TICK: Duration = Duration::from_secs(15);
+
+/// How often venue metadata is re-fetched for pending jobs
+const VENUE_REFRESH_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Clone)]
+pub struct DaemonOptions {
+    pub config_path: PathBuf,
+    pub log_path: PathBuf,
+    /// How long before a release to load credentials, warm up connections and sync clocks
+    pub warmup: Duration,
+    /// Serve the control API on this loopback address
+    pub api_addr: Option<SocketAddr>,
+    /// Shared secret the control API requires as a bearer token
+    pub api_token: String,
+    /// Request rate and budget for all jobs together (the config's, with overrides)
+    pub rate_limit: RateLimitConfig,
+}
+
+/// What every job run by one process shares
+#[derive(Clone)]
+pub struct JobResources {
+    pub credentials: Arc<CredentialCache>,
+    /// Concurrent jobs draw on one request rate and budget
+    pub limiter: Arc<RateLimiter>,
+}
+
+/// A job the daemon is running right now
+pub struct RunningJob {
+    handle: JoinHandle<JobStatus>,
+    pub started_at: DateTime<Utc>,
+    pub log_path: PathBuf,
+    pub stats: Arc<EngineStats>,
+}
+
+impl RunningJob {
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+}
+
+/// State shared by the scheduler loop and the control API
+pub struct DaemonState {
+    pub store: JobStore,
+    pub running: Mutex<HashMap<String, RunningJob>>,
+    /// Wakes the scheduler early, e.g. after a job is submitted or resumed
+    pub wake: Notify,
+    pub config_path: PathBuf,
+    pub log_path: PathBuf,
+    /// Interactive profiles are loaded at startup; nothing prompts after that
+    pub credentials: Arc<CredentialCache>,
+    pub limiter: Arc<RateLimiter>,
+}
+
+impl DaemonState {
+    /// Abort a running job; returns whether it was running
+    pub fn stop(&self, id: &str) -> bool {
+        match self.running.lock().unwrap().remove(id) {
+            Some(job) => {
+                job.handle.abort();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Run scheduled jobs from the job store as their releases come up, until interrupted
+pub async fn run(options: DaemonOptions, logger: LoggerHandle) -> Result<()> {
+    let store = JobStore::open_default();
+
+    // Ask for stdin tokens and passphrases now, while someone is there to answer
+    let credentials = CredentialCache::unattended();
+    let config = Config::load(&options.config_path)?;
+    credentials.preload(&config, &[])?;
+    for job in store.list()? {
+        if matches!(job.status, JobStatus::Pending | JobStatus::Paused | JobStatus::Running) {
+            credentials.preload(&config, &job.profiles)?;
+        }
+    }
+
+    let state = Arc::new(DaemonState {
+        store,
+        running: Mutex::new(HashMap::new()),
+        wake: Notify::new(),
+        config_path: options.config_path.clone(),
+        log_path: options.log_path.clone(),
+        credentials: Arc::new(credentials),
+        limiter: Arc::new(RateLimiter::new(options.rate_limit.clone())),
+    });
+    let store = &state.store;
+    let mut refreshed: HashMap<String, Instant> = HashMap::new();
+    // Jobs another process is running, so that's only logged once
+    let mut claimed_elsewhere: HashSet<String> = HashSet::new();
+
+    logger.log(&format!("🤖 Daemon started (warm-up {}s before each release)", options.warmup.as_secs()));
+
+    if let Some(addr) = options.api_addr {
+        let listener = control::bind(addr).await?;
+        logger.log(&format!("🔌 Control API listening on http://{}", addr));
+        tokio::spawn(control::serve(listener, options.api_token.clone(), Arc::clone(&state), logger.clone()));
+    }
+
+    loop {
+        state.running.lock().unwrap().retain(|_, job| !job.is_finished());
+
+        let jobs = match store.list() {
+            Ok(jobs) => jobs,
+            Err(e) => {
+                logger.log(&format!("⚠️  Could not load jobs: {:#}", e));
+                Vec::new()
+            }
+        };
+
+        for mut job in jobs {
+            let is_running = state.running.lock().unwrap().contains_key(&job.id);
+            if is_running || !matches!(job.status, JobStatus::Pending | JobStatus::Running) {
+                continue;
+            }
+
+            // Skip jobs a `schedule run` timer or another daemon is running. A job
+            // left "running" by a process that died is unlocked and picked up again.
+            let lock = match store.try_lock(&job.id) {
+                Ok(Some(lock)) => lock,
+                Ok(None) => {
+                    if claimed_elsewhere.insert(job.id.clone()) {
+                        logger.log(&format!("⏭️  {}: being run by another process, skipping", job.id));
+                    }
+                    continue;
+                }
+                Err(e) => {
+                    logger.log(&format!("⚠️  {}: {:#}", job.id, e));
+                    continue;
+                }
+            };
+            claimed_elsewhere.remove(&job.id);
+
+            let until_release = job.release_at - Utc::now();
+            let missed = job.strategy == Strategy::Competitive
+                && -until_release > chrono::Duration::seconds(job.poll_timeout_secs as i64);
+            if missed {
+                logger.log(&format!("❌ {}: missed the release at {}", job.id, job.release_local()));
+                record(store, &mut job, JobStatus::Failed, "Missed the release (daemon was not running)".to_string(), None, None);
+                continue;
+            }
+
+            if until_release.to_std().is_ok_and(|wait| wait > options.warmup) {
+                let stale = refreshed.get(&job.id).is_none_or(|at| at.elapsed() >= VENUE_REFRESH_INTERVAL);
+                if stale {
+                    refreshed.insert(job.id.clone(), Instant::now());
+                    if let Err(e) = refresh_venue(store, &mut job, &options.config_path, &state.credentials, &logger).await {
+                        logger.log(&format!("⚠️  {}: could not refresh venue: {:#}", job.id, e));
+                    }
+                }
+                continue;
+            }
+
+            logger.log(&format!("▶️  Starting {} ({} on {}, release {})",
+                job.id, job.venue_label(), job.dates.join(", "), job.release_local()));
+            let id = job.id.clone();
+            let log_path = get_default_log_path(&format!("job_{}", job.id));
+            let stats = Arc::new(EngineStats::default());
+            let handle = tokio::spawn(run_job(
+                job,
+                lock,
+                options.config_path.clone(),
+                log_path.clone(),
+                Arc::clone(&stats),
+                JobResources {
+                    credentials: Arc::clone(&state.credentials),
+                    limiter: Arc::clone(&state.limiter),
+                },
+                logger.clone(),
+            ));
+            state.running.lock().unwrap().insert(id, RunningJob {
+                handle,
+                started_at: Utc::now(),
+                log_path,
+                stats,
+            });
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(TICK) => {}
+            _ = state.wake.notified() => {}
+            _ = tokio::signal::ctrl_c() => {
+                let mut running = state.running.lock().unwrap();
+                running.retain(|_, job| !job.is_finished());
+                logger.log(&format!("⏹️  Daemon stopped ({} running jobs interrupted)", running.len()));
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Update the venue name and, when it came from the venue, the lead time and release instant
+async fn refresh_venue(
+    store: &JobStore,
+    job: &mut Job,
+    config_path: &Path,
+    credentials: &CredentialCache,
+    logger: &LoggerHandle,
+) -> Result<()> {
+    let config = Config::load(config_path)?;
+    let accounts = credentials.resolve(&config, &job.profiles)?;
+    let client = ResyClient::new(accounts[0].clone())?.with_network(&config.network)?;
+    let venue = client.fetch_venue_details(&job.venue_id).await?;
+
+    let mut changed = job.venue_name.as_deref() != Some(venue.venue.name.as_str());
+    job.venue_name = Some(venue.venue.name);
+
+    if let (true, Some(lead_days), Some(release_time)) =
+        (job.lead_days_from_venue, venue.lead_time_in_days, job.release_time.clone())
+    {
+        if job.lead_days != Some(lead_days) {
+            job.release_at = schedule::earliest_release(&job.dates, lead_days, &release_time)?;
+            logger.log(&format!("🔄 {}: lead time is now {} days, release moved to {}",
+                job.id, lead_days, job.release_local()));
+            job.lead_days = Some(lead_days);
+            changed = true;
+        }
+    }
+
+    // Don't resurrect a job that was removed meanwhile
+    if changed && store.exists(&job.id) {
+        store.save(job)?;
+    }
+    Ok(())
+}
+
+pub fn record(store: &JobStore, job: &mut Job, status: JobStatus, message: String, reservation_id: Option<u64>, log_file: Option<PathBuf>) {
+    job.status = status;
+    job.outcome = Some(JobOutcome {
+        finished_at: Utc::now(),
+        message,
+        reservation_id,
+        log_file,
+    });
+    if store.exists(&job.id) {
+        let _ = store.save(job);
+    }
+}
+
+/// Run one job to completion, logging its details to `log_path` and a summary to
+/// `daemon_logger`, and record the outcome in the job store. `_lock` is held
+/// until the job finishes.
+pub async fn run_job(
+    job: Job,
+    _lock: JobLock,
+    config_path: PathBuf,
+    log_path: PathBuf,
+    stats: Arc<EngineStats>,
+    resources: JobResources,
+    daemon_logger: LoggerHandle,
+) -> JobStatus {
+    let store = JobStore::open_default();
+
+    // Pick up changes made since the job was read, and don't write back one
+    // that was removed meanwhile
+    let mut job = match store.get(&job.id) {
+        Ok(current) => current,
+        Err(_) if !store.exists(&job.id) => {
+            daemon_logger.log(&format!("🗑️  {}: removed before it started", job.id));
+            return JobStatus::Cancelled;
+        }
+        Err(e) => {
+            daemon_logger.log(&format!("⚠️  {}: could not reload job: {:#}", job.id, e));
+            job
+        }
+    };
+    job.status = JobStatus::Running;
+    if let Err(e) = store.save(&job) {
+        daemon_logger.log(&format!("⚠️  {}: could not update job: {:#}", job.id, e));
+    }
+
+    match execute(&job, &config_path, &log_path, stats, &resources).await {
+        Ok((message, reservation_id)) => {
+            daemon_logger.log(&format!("✅ {}: {}", job.id, message));
+            record(&store, &mut job, JobStatus::Booked, message, reservation_id, Some(log_path));
+        }
+        Err(e) => {
+            daemon_logger.log(&format!("❌ {}: {:#}", job.id, e));
+            record(&store, &mut job, JobStatus::Failed, format!("{:#}", e), None, Some(log_path));
+        }
+    }
+    job.status
+}
+
+/// Warm up, wait for the release and book. Returns a summary and the reservation ID.
+async fn execute(
+    job: &Job,
+    config_path: &Path,
+    log_path: &Path,
+    stats: Arc<EngineStats>,
+    resources: &JobResources,
+) -> Result<(String, Option<u64>)> {
+    let logger = Logger::new(log_path.to_path_buf())?;
+    let handle = logger.clone_handle();
+    handle.log(&format!("📅 Job {}: {} on {} for {}", job.id, job.venue_label(), job.dates.join(", "), job.party_size));
+
+    // Re-read the config so tokens refreshed since the job was added are used
+    let config = Config::load(config_path)?;
+    let accounts = resources.credentials.resolve(&config, &job.profiles)?;
+    check_token_expiry(&accounts, |line| logger.log(line))?;
+    let notifier = Notifier::from_config(&config, Vec::new(), Some(handle.clone()))?;
+    let clients: Vec<_> = build_clients_sharing(&accounts, &notifier, &config, &resources.limiter)?
+        .into_iter()
+        .map(|client| client.with_stats(Arc::clone(&stats)))
+        .collect();
+
+    // Resolve the API host now rather than during the release window. Watch
+    // jobs run for hours, so they keep resolving as usual.
+    let clients = match job.strategy {
+        Strategy::Competitive => dns::pin_clients(clients, &config.network, &handle).await,
+        Strategy::Watch => clients,
+    };
+
+    // Open connections and check auth ahead of the release
+    handle.log("🔥 Warming up...");
+    for client in &clients {
+        if let Err(e) = client.fetch_user().await {
+            handle.log(&format!("⚠️  {}: {}", client.profile(), e));
+        }
+    }
+
+    let offset = match clients[0].clock_offset().await {
+        Ok(offset) => {
+            handle.log(&format!("🕐 Resy clock is {:+.3}s from ours", offset.num_milliseconds() as f64 / 1000.0));
+            offset
+        }
+        Err(e) => {
+            handle.log(&format!("⚠️  Clock sync failed, using the local clock: {:#}", e));
+            chrono::Duration::zero()
+        }
+    };
+
+    // The release instant by our clock
+    let release = job.release_at - offset;
+    poll::wait_for_release(release, &job.poll_schedule, &handle).await;
+
+    let result = match job.strategy {
+        Strategy::Competitive => {
+            // Jobs are created with one date; an older job with several only books the first
+            if job.dates.len() > 1 {
+                handle.log(&format!("⚠️  Competitive jobs book one date; only trying {}", job.dates[0]));
+            }
+            let params = BookingParams {
+                release_at: Some(release),
+                retry: config.retry.clone(),
+                ..job.params(&job.dates[0])
+            };
+            match clients[0].book_competitive(&params, &clients, handle.clone()).await {
+                Ok(Some(slot)) => Ok((
+                    format!("Booked {} at {} ({})", job.venue_label(), slot.slot.date.start, slot.slot.config.slot_type),
+                    slot.confirmation.reservation_id,
+                )),
+                Ok(None) => Err(anyhow::anyhow!("Finished without booking")),
+                Err(e) => Err(e),
+            }
+        }
+        Strategy::Watch => {
+            let targets = job
+                .dates
+                .iter()
+                .map(|date| format!("{}:{}", job.venue_id, date).parse::<WatchTarget>())
+                .collect::<Result<Vec<_>>>()
+                .context("Invalid job dates")?;
+            let options = WatchOptions {
+                targets,
+                template: BookingParams {
+                    retry: config.retry.clone(),
+                    ..job.params(&job.dates[0])
+                },
+                interval: Duration::from_secs(job.watch_interval_secs.max(1)),
+                jitter: 0.2,
+                alert_only: false,
+                duration: None,
+            };
+            watch::watch(&clients, options, handle.clone())
+                .await
+                .map(|_| (format!("Booked {} by watching for cancellations", job.venue_label()), None))
+        }
+    };
+
+    notifier.flush().await;
+    result
+}
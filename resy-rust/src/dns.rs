@@ -0,0 +1,125 @@
+use anyhow::{Context, Result};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+use tokio::task::JoinSet;
+use tokio::time::timeout;
+
+use crate::api::ResyClient;
+use crate::config::NetworkConfig;
+use crate::LoggerHandle;
+
+/// Addresses that don't connect within this long lose the race
+const RACE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The API host's addresses, resolved ahead of the release window and used
+/// instead of DNS lookups for the rest of the run
+#[derive(Debug, Clone)]
+pub struct PinnedHost {
+    pub host: String,
+    /// Preferred first, with the TCP connect time when the addresses were raced
+    pub entries: Vec<(SocketAddr, Option<Duration>)>,
+}
+
+impl PinnedHost {
+    pub fn addrs(&self) -> Vec<SocketAddr> {
+        self.entries.iter().map(|(addr, _)| *addr).collect()
+    }
+
+    /// e.g. "52.1.2.3 (11ms), 52.4.5.6 (14ms)"
+    pub fn describe(&self) -> String {
+        self.entries
+            .iter()
+            .map(|(addr, connect)| match connect {
+                Some(connect) => format!("{} ({}ms)", addr.ip(), connect.as_millis()),
+                None => addr.ip().to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Resolve the API host, ordering its addresses by connect time when
+/// `race_connections` is set
+pub async fn resolve(network: &NetworkConfig) -> Result<PinnedHost> {
+    let (host, port) = network.host_port()?;
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), port))
+        .await
+        .context(format!("Could not resolve {}", host))?
+        .collect();
+    if addrs.is_empty() {
+        anyhow::bail!("{} has no addresses", host);
+    }
+
+    let entries = if network.race_connections && addrs.len() > 1 {
+        race(addrs).await
+    } else {
+        addrs.into_iter().map(|addr| (addr, None)).collect()
+    };
+    Ok(PinnedHost { host, entries })
+}
+
+/// Connect to every address at once. Those that connected come first, fastest
+/// first; the rest stay on as a last resort.
+async fn race(addrs: Vec<SocketAddr>) -> Vec<(SocketAddr, Option<Duration>)> {
+    let mut attempts = JoinSet::new();
+    for addr in addrs.iter().copied() {
+        attempts.spawn(async move {
+            let started = Instant::now();
+            timeout(RACE_TIMEOUT, TcpStream::connect(addr)).await.ok()?.ok()?;
+            Some((addr, started.elapsed()))
+        });
+    }
+
+    let mut connected = Vec::new();
+    while let Some(attempt) = attempts.join_next().await {
+        if let Ok(Some(result)) = attempt {
+            connected.push(result);
+        }
+    }
+    connected.sort_by_key(|(_, elapsed)| *elapsed);
+
+    let mut entries: Vec<_> = connected.iter().map(|(addr, elapsed)| (*addr, Some(*elapsed))).collect();
+    entries.extend(
+        addrs
+            .into_iter()
+            .filter(|addr| !connected.iter().any(|(winner, _)| winner == addr))
+            .map(|addr| (addr, None)),
+    );
+    entries
+}
+
+/// Pin every client to freshly resolved API addresses for the release window.
+/// Clients are returned as they were if pinning is off or resolving fails.
+pub async fn pin_clients(clients: Vec<ResyClient>, network: &NetworkConfig, logger: &LoggerHandle) -> Vec<ResyClient> {
+    let host = network.host_port().map(|(host, _)| host).unwrap_or_default();
+    if !network.pin_dns || host.parse::<IpAddr>().is_ok() {
+        return clients;
+    }
+
+    let started = Instant::now();
+    let pinned = match resolve(network).await {
+        Ok(pinned) => Arc::new(pinned),
+        Err(e) => {
+            logger.log(&format!("⚠️  DNS pre-resolution failed, resolving per connection: {:#}", e));
+            return clients;
+        }
+    };
+    let resolved_in = started.elapsed();
+
+    let pinned_clients = clients
+        .iter()
+        .map(|client| client.clone().with_pinned_host(Arc::clone(&pinned)))
+        .collect::<Result<Vec<_>>>();
+    match pinned_clients {
+        Ok(pinned_clients) => {
+            logger.log(&format!("📌 Pinned {} to {} (resolved in {}ms)", pinned.host, pinned.describe(), resolved_in.as_millis()));
+            pinned_clients
+        }
+        Err(e) => {
+            logger.log(&format!("⚠️  Could not pin {}: {:#}", pinned.host, e));
+            clients
+        }
+    }
+}
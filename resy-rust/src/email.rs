@@ -0,0 +1,142 @@
+use anyhow::{Context, Result};
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials as SmtpCredentials;
+use lettre::transport::smtp::client::{Tls, TlsParameters};
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use std::time::Duration;
+
+use crate::config::{SmtpConfig, SmtpSecurity};
+use crate::credentials;
+use crate::notify::{Event, EventKind};
+
+const SMTP_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Emails a summary of booking events over SMTP
+pub struct EmailSink {
+    config: SmtpConfig,
+    from: Mailbox,
+    to: Vec<Mailbox>,
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+}
+
+impl EmailSink {
+    pub fn new(config: SmtpConfig) -> Result<Self> {
+        let from: Mailbox = config
+            .from
+            .parse()
+            .context(format!("Invalid smtp.from address: {}", config.from))?;
+        let to = config
+            .to
+            .iter()
+            .map(|address| {
+                address
+                    .parse()
+                    .context(format!("Invalid smtp.to address: {}", address))
+            })
+            .collect::<Result<Vec<Mailbox>>>()?;
+        if to.is_empty() {
+            anyhow::bail!("smtp.to needs at least one recipient");
+        }
+
+        let port = config.port.unwrap_or(match config.security {
+            SmtpSecurity::Tls => 465,
+            SmtpSecurity::Starttls => 587,
+            SmtpSecurity::None => 25,
+        });
+        let tls = match config.security {
+            SmtpSecurity::None => Tls::None,
+            SmtpSecurity::Starttls => Tls::Required(TlsParameters::new(config.host.clone())?),
+            SmtpSecurity::Tls => Tls::Wrapper(TlsParameters::new(config.host.clone())?),
+        };
+
+        let mut builder = AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.host)
+            .port(port)
+            .tls(tls)
+            .timeout(Some(SMTP_TIMEOUT));
+        if let Some(username) = &config.username {
+            let password = match (&config.password, &config.password_command) {
+                (Some(password), _) => password.clone(),
+                (None, Some(command)) => credentials::run_secret_command(command)
+                    .context("Failed to get SMTP password")?,
+                (None, None) => anyhow::bail!("smtp.username is set but no password or password_command"),
+            };
+            builder = builder.credentials(SmtpCredentials::new(username.clone(), password));
+        }
+
+        Ok(Self {
+            from,
+            to,
+            transport: builder.build(),
+            config,
+        })
+    }
+
+    pub fn name(&self) -> String {
+        format!("email {}", self.config.to.join(", "))
+    }
+
+    pub fn accepts(&self, kind: EventKind) -> bool {
+        self.config.events.is_empty() || self.config.events.contains(&kind)
+    }
+
+    fn subject(event: &Event) -> String {
+        let venue = event.venue_name.as_deref().unwrap_or("Resy");
+        let date = event.date.as_deref().unwrap_or_default();
+        match event.event {
+            EventKind::Booked => format!("✅ Booked {} on {}", venue, date),
+            EventKind::Failed => format!("❌ Booking failed: {} on {}", venue, date),
+            EventKind::SlotsFound => format!("🔔 Slots available: {} on {}", venue, date),
+            EventKind::AuthError => "⚠️ Resy rejected your credentials".to_string(),
+        }
+    }
+
+    fn body(event: &Event) -> String {
+        let mut lines = vec![event.summary.clone(), String::new()];
+
+        let mut field = |label: &str, value: Option<String>| {
+            if let Some(value) = value.filter(|v| !v.is_empty()) {
+                lines.push(format!("{}: {}", label, value));
+            }
+        };
+        field("Restaurant", event.venue_name.clone());
+        field("Venue ID", event.venue_id.clone());
+        field("Date", event.date.clone());
+        field("Party size", event.party_size.map(|p| p.to_string()));
+        field("Account", event.profile.clone());
+        if let Some(confirmation) = &event.confirmation {
+            field("Reservation ID", confirmation.reservation_id.map(|id| id.to_string()));
+            field("Resy token", confirmation.resy_token.clone());
+        }
+        field("Error", event.error.clone());
+
+        if !event.slots.is_empty() {
+            lines.push(String::new());
+            lines.push(if event.event == EventKind::Booked { "Slot:" } else { "Slots:" }.to_string());
+            for slot in &event.slots {
+                lines.push(format!("  - {} ({})", slot.start, slot.slot_type));
+            }
+        }
+
+        lines.push(String::new());
+        lines.push(format!("Sent by resy-rust at {}", event.timestamp));
+        lines.join("\n")
+    }
+
+    pub async fn deliver(&self, event: &Event) -> Result<()> {
+        let mut builder = Message::builder()
+            .from(self.from.clone())
+            .subject(Self::subject(event));
+        for to in &self.to {
+            builder = builder.to(to.clone());
+        }
+        let message = builder
+            .body(Self::body(event))
+            .context("Failed to build email")?;
+
+        self.transport
+            .send(message)
+            .await
+            .context(format!("Failed to send email via {}", self.config.host))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,122 @@
+use anyhow::{Context, Result};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::notify::{Event, EventKind, Sink};
+
+/// Event fields exported to hooks as RESY_<NAME> environment variables
+/// Default for --hook-timeout-secs and a job file's hook_timeout_secs
+pub const DEFAULT_HOOK_TIMEOUT_SECS: u64 = 30;
+
+const ENV_FIELDS: &[&str] = &[
+    "event",
+    "timestamp",
+    "summary",
+    "profile",
+    "venue_id",
+    "venue_name",
+    "date",
+    "party_size",
+    "slot_time",
+    "slot_type",
+    "slots",
+    "reservation_id",
+    "resy_token",
+    "error",
+];
+
+/// Local commands to run when engine events happen
+#[derive(clap::Args, Debug, Clone)]
+pub struct HookArgs {
+    /// Shell command to run after a successful booking
+    #[arg(long)]
+    pub on_success: Option<String>,
+
+    /// Shell command to run when booking fails
+    #[arg(long)]
+    pub on_failure: Option<String>,
+
+    /// Shell command to run when matching slots are found
+    #[arg(long)]
+    pub on_slots_found: Option<String>,
+
+    /// Kill a hook command that runs longer than this many seconds
+    #[arg(long, default_value_t = DEFAULT_HOOK_TIMEOUT_SECS)]
+    pub hook_timeout_secs: u64,
+}
+
+impl HookArgs {
+    pub fn sinks(&self) -> Vec<Sink> {
+        let timeout = Duration::from_secs(self.hook_timeout_secs);
+        [
+            (EventKind::Booked, &self.on_success),
+            (EventKind::Failed, &self.on_failure),
+            (EventKind::SlotsFound, &self.on_slots_found),
+        ]
+        .into_iter()
+        .filter_map(|(kind, command)| {
+            command.as_ref().map(|command| {
+                Sink::Hook(HookSink {
+                    command: command.clone(),
+                    kind,
+                    timeout,
+                })
+            })
+        })
+        .collect()
+    }
+}
+
+/// Runs a shell command for one kind of event, passing the event as
+/// RESY_* environment variables and as JSON on stdin
+pub struct HookSink {
+    command: String,
+    kind: EventKind,
+    timeout: Duration,
+}
+
+impl HookSink {
+    pub fn name(&self) -> String {
+        format!("{} hook `{}`", self.kind.as_str(), self.command)
+    }
+
+    pub fn accepts(&self, kind: EventKind) -> bool {
+        kind == self.kind
+    }
+
+    pub async fn deliver(&self, event: &Event) -> Result<()> {
+        let mut command = Command::new("sh");
+        command
+            .arg("-c")
+            .arg(&self.command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        for field in ENV_FIELDS {
+            let value = event.template_value(field).unwrap_or_default();
+            command.env(format!("RESY_{}", field.to_uppercase()), value);
+        }
+
+        let mut child = command.spawn().context("Failed to start hook")?;
+        let payload = serde_json::to_vec(event).context("Failed to serialize event")?;
+        if let Some(mut stdin) = child.stdin.take() {
+            // A hook that ignores stdin may exit before reading it
+            let _ = stdin.write_all(&payload).await;
+        }
+
+        // Dropping the child on timeout kills it
+        let output = tokio::time::timeout(self.timeout, child.wait_with_output())
+            .await
+            .map_err(|_| anyhow::anyhow!("Hook timed out after {}s and was killed", self.timeout.as_secs()))?
+            .context("Failed to wait for hook")?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("Hook exited with {}: {}", output.status, stderr.trim());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,500 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::api::{self, BookingParams, DryRun, ResyClient};
+use crate::breaker;
+use crate::config::Config;
+use crate::dns;
+use crate::hook::{self, HookArgs};
+use crate::notify::Notifier;
+use crate::poll::{self, PollSchedule};
+use crate::policy::PolicyLimits;
+use crate::watch::WatchTarget;
+use crate::{get_default_log_path, Logger};
+
+/// `dry_run = true` for a shallow dry run, or `dry_run = "deep"`
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(untagged, expecting = "true, false, \"shallow\" or \"deep\"")]
+enum DryRunSetting {
+    Enabled(bool),
+    Mode(DryRun),
+}
+
+impl DryRunSetting {
+    fn mode(self) -> Option<DryRun> {
+        match self {
+            DryRunSetting::Enabled(enabled) => enabled.then_some(DryRun::Shallow),
+            DryRunSetting::Mode(mode) => Some(mode),
+        }
+    }
+}
+
+/// One `[[jobs]]` entry, or the `[defaults]` every entry inherits from.
+/// Fields mirror the `book` options.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Entry {
+    name: Option<String>,
+    venue_id: Option<String>,
+    date: Option<String>,
+    /// Extra venue/date pairs as VENUE_ID:YYYY-MM-DD, tried in order
+    targets: Option<Vec<String>>,
+    party_size: Option<u32>,
+    times: Option<Vec<String>>,
+    types: Option<Vec<String>>,
+    dry_run: Option<DryRunSetting>,
+    threads: Option<usize>,
+    retries: Option<usize>,
+    poll_interval_ms: Option<u64>,
+    poll_timeout_secs: Option<u64>,
+    /// Local YYYY-MM-DD HH:MM:SS the job waits for and centres polling on
+    release_at: Option<String>,
+    poll_lead_secs: Option<u64>,
+    poll_sparse_ms: Option<u64>,
+    poll_window_before_secs: Option<u64>,
+    poll_window_after_secs: Option<u64>,
+    poll_backoff_max_ms: Option<u64>,
+    log_file: Option<PathBuf>,
+    payment_method_id: Option<u64>,
+    max_cancellation_fee: Option<f64>,
+    max_deposit: Option<f64>,
+    on_success: Option<String>,
+    on_failure: Option<String>,
+    on_slots_found: Option<String>,
+    hook_timeout_secs: Option<u64>,
+}
+
+impl Entry {
+    /// Fill unset fields from `defaults`
+    fn or(self, defaults: &Entry) -> Entry {
+        let d = defaults.clone();
+        Entry {
+            name: self.name,
+            venue_id: self.venue_id.or(d.venue_id),
+            date: self.date.or(d.date),
+            targets: self.targets.or(d.targets),
+            party_size: self.party_size.or(d.party_size),
+            times: self.times.or(d.times),
+            types: self.types.or(d.types),
+            dry_run: self.dry_run.or(d.dry_run),
+            threads: self.threads.or(d.threads),
+            retries: self.retries.or(d.retries),
+            poll_interval_ms: self.poll_interval_ms.or(d.poll_interval_ms),
+            poll_timeout_secs: self.poll_timeout_secs.or(d.poll_timeout_secs),
+            release_at: self.release_at.or(d.release_at),
+            poll_lead_secs: self.poll_lead_secs.or(d.poll_lead_secs),
+            poll_sparse_ms: self.poll_sparse_ms.or(d.poll_sparse_ms),
+            poll_window_before_secs: self.poll_window_before_secs.or(d.poll_window_before_secs),
+            poll_window_after_secs: self.poll_window_after_secs.or(d.poll_window_after_secs),
+            poll_backoff_max_ms: self.poll_backoff_max_ms.or(d.poll_backoff_max_ms),
+            log_file: self.log_file,
+            payment_method_id: self.payment_method_id.or(d.payment_method_id),
+            max_cancellation_fee: self.max_cancellation_fee.or(d.max_cancellation_fee),
+            max_deposit: self.max_deposit.or(d.max_deposit),
+            on_success: self.on_success.or(d.on_success),
+            on_failure: self.on_failure.or(d.on_failure),
+            on_slots_found: self.on_slots_found.or(d.on_slots_found),
+            hook_timeout_secs: self.hook_timeout_secs.or(d.hook_timeout_secs),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JobFile {
+    /// Profiles to book with when --profile isn't given
+    #[serde(default)]
+    profiles: Vec<String>,
+    #[serde(default)]
+    defaults: Entry,
+    #[serde(default)]
+    jobs: Vec<Entry>,
+}
+
+/// A validated job from a job file
+#[derive(Debug, Clone)]
+pub struct FileJob {
+    pub name: String,
+    /// Venue/date pairs, tried in order until one books
+    pub targets: Vec<WatchTarget>,
+    /// Booking settings; venue_id and day are replaced per target
+    pub template: BookingParams,
+    pub log_file: Option<PathBuf>,
+    pub hooks: HookArgs,
+}
+
+/// The contents of a job file
+#[derive(Debug, Clone)]
+pub struct Batch {
+    pub profiles: Vec<String>,
+    pub jobs: Vec<FileJob>,
+}
+
+impl Batch {
+    /// Override every job's dry-run mode, e.g. with `book --dry-run`
+    pub fn set_dry_run(&mut self, dry_run: Option<DryRun>) {
+        for job in &mut self.jobs {
+            job.template.dry_run = dry_run;
+        }
+    }
+}
+
+/// Read and validate a TOML or YAML (.yaml/.yml) job file, reporting every problem at once
+pub fn load(path: &Path) -> Result<Batch> {
+    let contents = fs::read_to_string(path).context(format!("Failed to read job file {}", path.display()))?;
+    let file = parse(path, &contents).context(format!("Invalid job file {}", path.display()))?;
+
+    if file.jobs.is_empty() {
+        anyhow::bail!("Job file {} has no [[jobs]]", path.display());
+    }
+
+    let mut jobs = Vec::new();
+    let mut errors = Vec::new();
+    for (index, entry) in file.jobs.into_iter().enumerate() {
+        let label = match &entry.name {
+            Some(name) => format!("jobs[{}] ({})", index, name),
+            None => format!("jobs[{}]", index),
+        };
+        match validate(entry.or(&file.defaults), index) {
+            Ok(job) => jobs.push(job),
+            Err(problems) => errors.extend(problems.into_iter().map(|p| format!("{}: {}", label, p))),
+        }
+    }
+
+    if !errors.is_empty() {
+        anyhow::bail!("Job file {} has {} problem(s):\n  {}", path.display(), errors.len(), errors.join("\n  "));
+    }
+    Ok(Batch {
+        profiles: file.profiles,
+        jobs,
+    })
+}
+
+/// Parse a job file as YAML for .yaml/.yml, and as TOML otherwise
+fn parse(path: &Path, contents: &str) -> Result<JobFile> {
+    let is_yaml = path
+        .extension()
+        .is_some_and(|ext| ext == "yaml" || ext == "yml");
+    if is_yaml {
+        Ok(serde_yaml_ng::from_str(contents)?)
+    } else {
+        Ok(toml::from_str(contents)?)
+    }
+}
+
+fn validate(entry: Entry, index: usize) -> std::result::Result<FileJob, Vec<String>> {
+    let mut problems = Vec::new();
+
+    let mut targets = Vec::new();
+    match (&entry.venue_id, &entry.date) {
+        (Some(venue_id), Some(date)) => match format!("{}:{}", venue_id, date).parse::<WatchTarget>() {
+            Ok(target) => targets.push(target),
+            Err(_) => problems.push(format!("invalid date '{}': expected YYYY-MM-DD", date)),
+        },
+        (Some(_), None) => problems.push("venue_id is set but date is missing".to_string()),
+        (None, Some(_)) => problems.push("date is set but venue_id is missing".to_string()),
+        (None, None) => {}
+    }
+    for (i, target) in entry.targets.iter().flatten().enumerate() {
+        match target.parse::<WatchTarget>() {
+            Ok(target) => targets.push(target),
+            Err(e) => problems.push(format!("targets[{}]: {}", i, e)),
+        }
+    }
+    if targets.is_empty() && problems.is_empty() {
+        problems.push("needs venue_id and date, or targets".to_string());
+    }
+
+    let party_size = entry.party_size.unwrap_or_default();
+    if party_size == 0 {
+        problems.push("party_size is missing or 0".to_string());
+    }
+
+    let times = entry.times.unwrap_or_default();
+    for (i, time) in times.iter().enumerate() {
+        if chrono::NaiveTime::parse_from_str(time, "%H:%M:%S").is_err() {
+            problems.push(format!("times[{}]: invalid time '{}': expected HH:MM:SS", i, time));
+        }
+    }
+
+    let threads = entry.threads.unwrap_or(api::DEFAULT_THREADS);
+    if threads == 0 {
+        problems.push("threads must be at least 1".to_string());
+    }
+    let retries = entry.retries.unwrap_or(api::DEFAULT_RETRIES);
+    let poll_interval_ms = entry.poll_interval_ms.unwrap_or(api::DEFAULT_POLL_INTERVAL_MS);
+    if poll_interval_ms == 0 {
+        problems.push("poll_interval_ms must be at least 1".to_string());
+    }
+    let defaults = PollSchedule::default();
+    let poll_schedule = PollSchedule {
+        lead_secs: entry.poll_lead_secs.unwrap_or(defaults.lead_secs),
+        sparse_ms: entry.poll_sparse_ms.unwrap_or(defaults.sparse_ms),
+        window_before_secs: entry.poll_window_before_secs.unwrap_or(defaults.window_before_secs),
+        window_after_secs: entry.poll_window_after_secs.unwrap_or(defaults.window_after_secs),
+        backoff_max_ms: entry.poll_backoff_max_ms.unwrap_or(defaults.backoff_max_ms),
+    };
+    if let Err(e) = poll_schedule.validate() {
+        problems.push(e.to_string());
+    }
+    let release_at = match entry.release_at.as_deref().map(crate::parse_local_datetime).transpose() {
+        Ok(release_at) => release_at,
+        Err(e) => {
+            problems.push(e.to_string());
+            None
+        }
+    };
+    for (field, value) in [
+        ("max_cancellation_fee", entry.max_cancellation_fee),
+        ("max_deposit", entry.max_deposit),
+    ] {
+        if value.is_some_and(|v| v.is_nan() || v < 0.0) {
+            problems.push(format!("{} must be a non-negative amount", field));
+        }
+    }
+    let hook_timeout_secs = entry.hook_timeout_secs.unwrap_or(hook::DEFAULT_HOOK_TIMEOUT_SECS);
+    if hook_timeout_secs == 0 {
+        problems.push("hook_timeout_secs must be at least 1".to_string());
+    }
+
+    if !problems.is_empty() {
+        return Err(problems);
+    }
+
+    let name = entry.name.unwrap_or_else(|| format!("job {}", index));
+    let first = targets[0].clone();
+    Ok(FileJob {
+        name,
+        targets,
+        template: BookingParams {
+            venue_id: first.venue_id,
+            party_size,
+            day: first.day,
+            times,
+            types: entry.types.unwrap_or_default(),
+            dry_run: entry.dry_run.and_then(DryRunSetting::mode),
+            num_threads: threads,
+            num_retries: retries,
+            retry: Default::default(),
+            poll_interval: Duration::from_millis(poll_interval_ms),
+            poll_timeout: Duration::from_secs(entry.poll_timeout_secs.unwrap_or(api::DEFAULT_POLL_TIMEOUT_SECS)),
+            poll_schedule,
+            release_at,
+            payment_method_id: entry.payment_method_id,
+            policy_limits: PolicyLimits {
+                max_cancellation_fee: entry.max_cancellation_fee,
+                max_deposit: entry.max_deposit,
+            },
+        },
+        log_file: entry.log_file,
+        hooks: HookArgs {
+            on_success: entry.on_success,
+            on_failure: entry.on_failure,
+            on_slots_found: entry.on_slots_found,
+            hook_timeout_secs,
+        },
+    })
+}
+
+/// Run every job concurrently over the same clients, each trying its targets in
+/// order. Fails if any job didn't book.
+pub async fn run(batch: Batch, clients: &[ResyClient], config: &Config) -> Result<()> {
+    let mut handles = Vec::new();
+
+    for job in batch.jobs {
+        let log_path = job
+            .log_file
+            .clone()
+            .unwrap_or_else(|| get_default_log_path(&format!("venue_{}", job.targets[0].venue_id)));
+        let logger = Logger::new(log_path.clone())?;
+        let handle = logger.clone_handle();
+        let notifier = Notifier::from_config(config, job.hooks.sinks(), Some(handle.clone()))?;
+        // Same connection pools and sessions, but this job's notification sinks
+        let clients: Vec<_> = clients
+            .iter()
+            .map(|client| client.clone().with_notifier(notifier.clone()))
+            .collect();
+
+        handle.log(&format!("🚀 {}: {} target(s), party of {}, log {}",
+            job.name, job.targets.len(), job.template.party_size, log_path.display()));
+
+        let retry = config.retry.clone();
+        let network = config.network.clone();
+        handles.push(tokio::spawn(async move {
+            if let Some(release) = job.template.release_at {
+                poll::wait_for_release(release, &job.template.poll_schedule, &handle).await;
+            }
+            let clients = dns::pin_clients(clients, &network, &handle).await;
+            let mut result = Err(anyhow::anyhow!("No targets"));
+            for target in &job.targets {
+                let params = BookingParams {
+                    venue_id: target.venue_id.clone(),
+                    day: target.day.clone(),
+                    retry: retry.clone(),
+                    ..job.template.clone()
+                };
+                handle.log(&format!("🎯 {}: venue {} on {}", job.name, target.venue_id, target.day));
+                result = clients[0].book_competitive(&params, &clients, handle.clone()).await;
+                if result.as_ref().map_or_else(breaker::is_fatal, |_| true) {
+                    break;
+                }
+            }
+            notifier.flush().await;
+            (job.name, result)
+        }));
+    }
+
+    let mut failed = 0;
+    for handle in handles {
+        let (name, result) = handle.await.context("Job task panicked")?;
+        match result {
+            Ok(_) => println!("✅ {}: done", name),
+            Err(e) => {
+                failed += 1;
+                println!("❌ {}: {}", name, e);
+            }
+        }
+    }
+
+    if failed > 0 {
+        anyhow::bail!("{} job(s) failed", failed);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(toml: &str) -> Entry {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn validate_fills_defaults() {
+        let job = validate(entry("venue_id = \"123\"\ndate = \"2026-01-01\"\nparty_size = 2"), 3).unwrap();
+        assert_eq!(job.name, "job 3");
+        assert_eq!(job.targets.len(), 1);
+        assert_eq!(job.template.venue_id, "123");
+        assert_eq!(job.template.day, "2026-01-01");
+        assert_eq!(job.template.num_threads, 5);
+        assert_eq!(job.template.poll_interval, Duration::from_millis(250));
+        assert_eq!(job.template.poll_schedule, PollSchedule::default());
+        assert_eq!(job.hooks.hook_timeout_secs, 30);
+    }
+
+    #[test]
+    fn validate_keeps_target_order() {
+        let job = validate(
+            entry("venue_id = \"1\"\ndate = \"2026-01-01\"\ntargets = [\"2:2026-01-02\", \"3:2026-01-03\"]\nparty_size = 4"),
+            0,
+        )
+        .unwrap();
+        let targets: Vec<_> = job.targets.iter().map(|t| (t.venue_id.as_str(), t.day.as_str())).collect();
+        assert_eq!(targets, [("1", "2026-01-01"), ("2", "2026-01-02"), ("3", "2026-01-03")]);
+    }
+
+    #[test]
+    fn validate_reports_every_problem() {
+        let problems = validate(
+            entry(
+                r#"
+                venue_id = "123"
+                targets = ["456", "789:2026-13-01"]
+                times = ["19:00:00", "7pm"]
+                threads = 0
+                poll_interval_ms = 0
+                poll_sparse_ms = 0
+                release_at = "tomorrow"
+                max_deposit = -5.0
+                hook_timeout_secs = 0
+                "#,
+            ),
+            0,
+        )
+        .unwrap_err();
+
+        let expected = [
+            "venue_id is set but date is missing",
+            "targets[0]: Invalid target '456'",
+            "targets[1]: Invalid date in target '789:2026-13-01'",
+            "party_size is missing or 0",
+            "times[1]: invalid time '7pm'",
+            "threads must be at least 1",
+            "poll_interval_ms must be at least 1",
+            "Poll intervals must be at least 1ms",
+            "max_deposit must be a non-negative amount",
+            "hook_timeout_secs must be at least 1",
+        ];
+        for text in expected {
+            assert!(problems.iter().any(|p| p.starts_with(text)), "missing '{}' in {:#?}", text, problems);
+        }
+        // Plus one for release_at
+        assert_eq!(problems.len(), expected.len() + 1, "{:#?}", problems);
+    }
+
+    #[test]
+    fn validate_needs_a_target() {
+        let problems = validate(entry("party_size = 2"), 0).unwrap_err();
+        assert_eq!(problems, ["needs venue_id and date, or targets"]);
+    }
+
+    #[test]
+    fn yaml_and_toml_job_files_match() {
+        let toml = r#"
+            profiles = ["me"]
+
+            [defaults]
+            party_size = 2
+            times = ["19:00:00"]
+            dry_run = "deep"
+
+            [[jobs]]
+            name = "carbone"
+            venue_id = "6194"
+            date = "2026-01-01"
+
+            [[jobs]]
+            targets = ["58326:2026-01-02"]
+            threads = 3
+        "#;
+        let yaml = r#"
+profiles: [me]
+defaults:
+  party_size: 2
+  times: ["19:00:00"]
+  dry_run: deep
+jobs:
+  - name: carbone
+    venue_id: "6194"
+    date: "2026-01-01"
+  - targets: ["58326:2026-01-02"]
+    threads: 3
+"#;
+        let from_toml = parse(Path::new("jobs.toml"), toml).unwrap();
+        let from_yaml = parse(Path::new("jobs.yml"), yaml).unwrap();
+        assert_eq!(from_yaml.profiles, from_toml.profiles);
+        assert_eq!(from_yaml.jobs.len(), 2);
+
+        for (index, (y, t)) in from_yaml.jobs.into_iter().zip(from_toml.jobs).enumerate() {
+            let y = validate(y.or(&from_yaml.defaults), index).unwrap();
+            let t = validate(t.or(&from_toml.defaults), index).unwrap();
+            assert_eq!(format!("{:?}", y), format!("{:?}", t));
+        }
+        assert!(parse(Path::new("jobs.yaml"), toml).is_err());
+    }
+
+    #[test]
+    fn entries_inherit_defaults() {
+        let defaults = entry("venue_id = \"1\"\nparty_size = 2\nthreads = 8\nlog_file = \"shared.log\"");
+        let merged = entry("date = \"2026-01-01\"\nthreads = 2").or(&defaults);
+        assert_eq!(merged.venue_id.as_deref(), Some("1"));
+        assert_eq!(merged.party_size, Some(2));
+        assert_eq!(merged.threads, Some(2));
+        // Each job gets its own log unless it names one
+        assert_eq!(merged.log_file, None);
+    }
+}
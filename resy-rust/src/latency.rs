@@ -0,0 +1,249 @@
+use anyhow::{Context, Result};
+use reqwest::Version;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::{sleep, timeout};
+
+use crate::api::ResyClient;
+use crate::config::NetworkConfig;
+use crate::ratelimit::RateLimiter;
+
+/// Give up on a single probe after this long
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Only recommend HTTP/2 when it's at least this much faster
+const HTTP2_MIN_GAIN: f64 = 0.1;
+
+/// How many samples to take and how far apart
+#[derive(Debug, Clone, Copy)]
+pub struct LatencyOptions {
+    pub samples: usize,
+    pub interval: Duration,
+}
+
+/// Timings of one request over a brand new connection
+#[derive(Debug, Clone, Copy)]
+pub struct Phases {
+    pub dns: Duration,
+    pub connect: Duration,
+    /// None for a plain http:// API base
+    pub tls: Option<Duration>,
+    /// From sending the request to the first byte of the response
+    pub first_byte: Duration,
+}
+
+impl Phases {
+    /// Everything before the request goes out
+    pub fn setup(&self) -> Duration {
+        self.connect + self.tls.unwrap_or_default()
+    }
+}
+
+/// min / median / p99 of a set of samples
+#[derive(Debug, Clone, Copy)]
+struct Summary {
+    min: Duration,
+    median: Duration,
+    p99: Duration,
+}
+
+impl Summary {
+    fn of(samples: &[Duration]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+        let p99 = ((sorted.len() as f64 * 0.99).ceil() as usize).max(1);
+        Some(Self {
+            min: sorted[0],
+            median: sorted[sorted.len() / 2],
+            p99: sorted[p99 - 1],
+        })
+    }
+
+    fn columns(&self) -> String {
+        format!("{:>9} {:>9} {:>9}", ms(self.min), ms(self.median), ms(self.p99))
+    }
+}
+
+fn ms(duration: Duration) -> String {
+    format!("{:.1}ms", duration.as_secs_f64() * 1000.0)
+}
+
+/// Measure every phase of a request to the API base, then compare HTTP/1.1
+/// with HTTP/2 over kept-alive connections
+pub async fn run(network: &NetworkConfig, limiter: &RateLimiter, options: LatencyOptions) -> Result<()> {
+    let (host, port) = network.host_port()?;
+
+    println!("📡 Latency to {} ({} samples)", network.base(), options.samples);
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), port))
+        .await
+        .context(format!("Could not resolve {}", host))?
+        .collect();
+    let listed: Vec<_> = addrs.iter().map(|addr| addr.ip().to_string()).collect();
+    println!("   {} resolves to {}", host, listed.join(", "));
+    println!();
+
+    let mut phases = Vec::new();
+    let mut failures = Vec::new();
+    for sample in 0..options.samples {
+        if sample > 0 {
+            sleep(options.interval).await;
+        }
+        limiter.acquire().await?;
+        match measure(network).await {
+            Ok(measured) => phases.push(measured),
+            Err(e) => failures.push(format!("{:#}", e)),
+        }
+    }
+    if phases.is_empty() {
+        anyhow::bail!("Every probe failed; last error: {}", failures.last().map_or("none", String::as_str));
+    }
+
+    println!("   {:<16} {:>9} {:>9} {:>9}", "Fresh connection", "min", "median", "p99");
+    let column = |pick: fn(&Phases) -> Option<Duration>| phases.iter().filter_map(pick).collect::<Vec<_>>();
+    let rows = [
+        ("DNS", column(|p| Some(p.dns))),
+        ("TCP connect", column(|p| Some(p.connect))),
+        ("TLS handshake", column(|p| p.tls)),
+        ("First byte", column(|p| Some(p.first_byte))),
+    ];
+    for (label, samples) in rows {
+        if let Some(summary) = Summary::of(&samples) {
+            println!("   {:<16} {}", label, summary.columns());
+        }
+    }
+    if !failures.is_empty() {
+        println!("   ⚠️  {} of {} probes failed: {}", failures.len(), options.samples, failures[failures.len() - 1]);
+    }
+    println!();
+
+    println!("   {:<16} {:>9} {:>9} {:>9} {:>9}", "Kept-alive", "first", "min", "median", "p99");
+    let http1 = compare(network, false, limiter, options).await;
+    let http2 = compare(network, true, limiter, options).await;
+    for (label, result) in [("HTTP/1.1", &http1), ("HTTP/2", &http2)] {
+        match result {
+            Ok(run) => {
+                let mut line = format!("   {:<16} {:>9} {}", label, ms(run.first), run.summary.columns());
+                if run.version != run.requested {
+                    line.push_str(&format!("  (server chose {:?})", run.version));
+                }
+                println!("{}", line);
+            }
+            Err(e) => println!("   {:<16} ❌ {:#}", label, e),
+        }
+    }
+    println!();
+
+    if let Ok(http1) = &http1 {
+        println!("💡 A poll takes ~{} over a warm connection; --poll-interval-ms much below that",
+            ms(http1.summary.median));
+        println!("   only queues requests. Each extra --threads connection costs ~{} to open.",
+            ms(Summary::of(&phases.iter().map(Phases::setup).collect::<Vec<_>>()).map_or(Duration::ZERO, |s| s.median)));
+        if let Ok(http2) = &http2 {
+            let gain = 1.0 - http2.summary.median.as_secs_f64() / http1.summary.median.as_secs_f64().max(f64::EPSILON);
+            if http2.version == Version::HTTP_2 && gain >= HTTP2_MIN_GAIN && !network.http2 {
+                println!("   HTTP/2 was {:.0}% faster; set `http2 = true` under [network] to use it.", gain * 100.0);
+            } else if network.http2 && (http2.version != Version::HTTP_2 || gain < 0.0) {
+                println!("   HTTP/2 isn't helping here; consider `http2 = false` under [network].");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Time each phase of a HEAD request to the API base over a new connection
+pub async fn measure(network: &NetworkConfig) -> Result<Phases> {
+    let url = network.url()?;
+    let (host, port) = network.host_port()?;
+    let host_header = match url.port() {
+        Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+        None => url.host_str().unwrap_or_default().to_string(),
+    };
+    let https = url.scheme() == "https";
+    timeout(PROBE_TIMEOUT, probe(&host, port, https, &host_header, url.path()))
+        .await
+        .map_err(|_| anyhow::anyhow!("Timed out after {}s", PROBE_TIMEOUT.as_secs()))?
+}
+
+/// The phases of a request, timed by hand so they can be told apart
+async fn probe(host: &str, port: u16, https: bool, host_header: &str, path: &str) -> Result<Phases> {
+    let started = Instant::now();
+    let addr = tokio::net::lookup_host((host, port))
+        .await
+        .context(format!("Could not resolve {}", host))?
+        .next()
+        .context(format!("{} has no addresses", host))?;
+    let dns = started.elapsed();
+
+    let started = Instant::now();
+    let tcp = TcpStream::connect(addr).await.context(format!("Could not connect to {}", addr))?;
+    tcp.set_nodelay(true)?;
+    let connect = started.elapsed();
+
+    let request = format!("HEAD {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n", path, host_header);
+    if !https {
+        let mut tcp = tcp;
+        let first_byte = first_byte(&mut tcp, &request).await?;
+        return Ok(Phases { dns, connect, tls: None, first_byte });
+    }
+
+    let started = Instant::now();
+    let connector = tokio_native_tls::TlsConnector::from(native_tls::TlsConnector::new()?);
+    let mut stream = connector.connect(host, tcp).await.context("TLS handshake failed")?;
+    let tls = Some(started.elapsed());
+
+    let first_byte = first_byte(&mut stream, &request).await?;
+    Ok(Phases { dns, connect, tls, first_byte })
+}
+
+async fn first_byte<S: AsyncRead + AsyncWrite + Unpin>(stream: &mut S, request: &str) -> Result<Duration> {
+    let started = Instant::now();
+    stream.write_all(request.as_bytes()).await?;
+    let mut byte = [0u8; 1];
+    stream.read_exact(&mut byte).await.context("Connection closed before a response")?;
+    Ok(started.elapsed())
+}
+
+/// Round trips over one kept-alive connection with the booking client's settings
+struct ProtocolRun {
+    requested: Version,
+    version: Version,
+    /// The first request, which also opens the connection
+    first: Duration,
+    summary: Summary,
+}
+
+async fn compare(network: &NetworkConfig, http2: bool, limiter: &RateLimiter, options: LatencyOptions) -> Result<ProtocolRun> {
+    let client = ResyClient::build_http_client(&NetworkConfig { http2, ..network.clone() }, None)?;
+    let base = network.base();
+
+    let mut version = Version::HTTP_11;
+    let mut first = Duration::ZERO;
+    let mut round_trips = Vec::new();
+    for sample in 0..=options.samples {
+        if sample > 0 {
+            sleep(options.interval).await;
+        }
+        limiter.acquire().await?;
+        let started = Instant::now();
+        let response = client.head(format!("{}/", base)).send().await.context("Request failed")?;
+        let elapsed = started.elapsed();
+        if sample == 0 {
+            version = response.version();
+            first = elapsed;
+        } else {
+            round_trips.push(elapsed);
+        }
+    }
+
+    Ok(ProtocolRun {
+        requested: if http2 { Version::HTTP_2 } else { Version::HTTP_11 },
+        version,
+        first,
+        summary: Summary::of(&round_trips).context("No samples")?,
+    })
+}
@@ -1,17 +1,47 @@
 mod api;
+mod breaker;
+mod config;
+mod control;
+mod credentials;
+mod daemon;
+mod dns;
+mod email;
+mod hook;
+mod jobfile;
+mod latency;
+mod notify;
+mod poll;
+mod policy;
+mod preflight;
+mod ratelimit;
+mod retry;
+mod schedule;
+mod systemd;
+mod token;
 mod types;
+mod vault;
+mod watch;
+mod webhook;
 
 use anyhow::{Context, Result};
 use clap::Parser;
-use std::env;
 use std::fs::{self, OpenOptions};
 use std::io::Write;
 use std::path::PathBuf;
+use std::process::ExitCode;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
-use chrono::Local;
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
 
-use api::ResyClient;
+use api::{BookingParams, DryRun, ResyClient};
+use config::{Config, CredentialSource};
+use credentials::{CredentialCache, Credentials};
+use notify::{EventKind, Notifier};
+use policy::PolicyLimits;
+use breaker::CircuitBreaker;
+use ratelimit::RateLimiter;
+use schedule::{JobStatus, JobStore, Strategy};
+use token::TokenStatus;
 
 /// Logger that writes to both stdout and a file
 pub struct Logger {
@@ -74,6 +104,25 @@ impl LoggerHandle {
 #[command(name = "resy-rust")]
 #[command(about = "Book Resy reservations from the command line", long_about = None)]
 struct Cli {
+    /// Config file path (default: ~/.resy-rust/config.toml)
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
+    /// Named profile(s) from the config file; several profiles can book at once
+    #[arg(long, global = true, value_delimiter = ',')]
+    profile: Vec<String>,
+
+    /// Maximum sustained requests per second to Resy across all threads (overrides rate_limit.requests_per_sec)
+    #[arg(long, global = true)]
+    max_rps: Option<f64>,
+
+    /// Stop after this many requests to Resy in one run (overrides rate_limit.request_budget)
+    #[arg(long, global = true)]
+    request_budget: Option<usize>,
+
+    #[command(flatten)]
+    retry: retry::RetryArgs,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -83,16 +132,91 @@ enum Commands {
     /// Book a reservation
     Book {
         /// Venue ID of the restaurant
-        #[arg(long)]
-        venue_id: String,
+        #[arg(long, required_unless_present = "job_file")]
+        venue_id: Option<String>,
 
         /// Party size for the reservation
-        #[arg(long)]
-        party_size: u32,
+        #[arg(long, required_unless_present = "job_file")]
+        party_size: Option<u32>,
 
         /// Reservation date (YYYY-MM-DD)
+        #[arg(long, required_unless_present = "job_file")]
+        date: Option<String>,
+
+        /// Run the jobs in a TOML or YAML (.yaml/.yml) job file instead; booking options
+        /// come from the file, except --dry-run, which applies to every job
+        #[arg(long, conflicts_with_all = [
+            "venue_id", "party_size", "date", "times", "types", "threads", "retries",
+            "poll_interval_ms", "poll_timeout_secs", "log_file", "payment_method_id",
+            "max_cancellation_fee", "max_deposit", "release_at", "PollSchedule", "HookArgs",
+        ])]
+        job_file: Option<PathBuf>,
+
+        /// Preferred reservation times (HH:MM:SS format, can specify multiple)
+        #[arg(long, value_delimiter = ',')]
+        times: Vec<String>,
+
+        /// Preferred reservation types (e.g., Indoor, Outdoor, can specify multiple)
+        #[arg(long, value_delimiter = ',')]
+        types: Option<Vec<String>>,
+
+        /// Dry run mode - fetch slots but don't book; `--dry-run=deep` also gets a
+        /// booking token and checks the payment method and policy
+        #[arg(long, value_enum, value_name = "MODE", num_args = 0..=1, require_equals = true, default_missing_value = "shallow")]
+        dry_run: Option<DryRun>,
+
+        /// Number of concurrent booking threads (recommended: 3-5)
+        #[arg(long, default_value_t = api::DEFAULT_THREADS)]
+        threads: usize,
+
+        /// Retries of each slot per booking thread after the first attempt (0 = a single attempt)
+        #[arg(long, default_value_t = api::DEFAULT_RETRIES)]
+        retries: usize,
+
+        /// Poll interval in milliseconds inside the release window
+        #[arg(long, default_value_t = api::DEFAULT_POLL_INTERVAL_MS)]
+        poll_interval_ms: u64,
+
+        /// Maximum time to poll for slots in seconds
+        #[arg(long, default_value_t = api::DEFAULT_POLL_TIMEOUT_SECS)]
+        poll_timeout_secs: u64,
+
+        /// Log file path (default: ~/.resy-rust/logs/<venue>_<timestamp>.log)
         #[arg(long)]
-        date: String,
+        log_file: Option<String>,
+
+        /// Payment method to book with (see `payment-methods`; default: the account's default card)
+        #[arg(long)]
+        payment_method_id: Option<u64>,
+
+        /// Skip slots whose cancellation/no-show fee exceeds this many dollars per person
+        #[arg(long)]
+        max_cancellation_fee: Option<f64>,
+
+        /// Skip slots that require a deposit or prepayment above this many dollars
+        #[arg(long)]
+        max_deposit: Option<f64>,
+
+        /// Predicted release (local YYYY-MM-DD HH:MM:SS): wait until --poll-lead-secs before it and centre the poll schedule on it
+        #[arg(long)]
+        release_at: Option<String>,
+
+        #[command(flatten)]
+        poll_schedule: poll::PollSchedule,
+
+        #[command(flatten)]
+        hooks: hook::HookArgs,
+    },
+
+    /// Watch venues for cancellations over hours or days, booking (or alerting on) matching slots
+    Watch {
+        /// Venue/date pairs to watch as VENUE_ID:YYYY-MM-DD (can specify multiple)
+        #[arg(long = "target", value_delimiter = ',', required = true)]
+        targets: Vec<watch::WatchTarget>,
+
+        /// Party size for the reservation
+        #[arg(long)]
+        party_size: u32,
 
         /// Preferred reservation times (HH:MM:SS format, can specify multiple)
         #[arg(long, value_delimiter = ',')]
@@ -102,53 +226,406 @@ enum Commands {
         #[arg(long, value_delimiter = ',')]
         types: Option<Vec<String>>,
 
-        /// Dry run mode - fetch slots but don't book
+        /// Seconds between rounds of checks
+        #[arg(long, default_value = "60")]
+        interval_secs: u64,
+
+        /// Random +/- percentage applied to each interval
+        #[arg(long, default_value = "20")]
+        jitter_pct: u8,
+
+        /// Stop after this many hours (default: until booked or interrupted)
+        #[arg(long)]
+        duration_hours: Option<f64>,
+
+        /// Only report matching slots, don't book them
         #[arg(long, default_value = "false")]
-        dry_run: bool,
+        alert_only: bool,
+
+        /// Dry run mode - report the first matching slots but don't book;
+        /// `--dry-run=deep` also gets a booking token for them
+        #[arg(long, value_enum, value_name = "MODE", num_args = 0..=1, require_equals = true, default_missing_value = "shallow")]
+        dry_run: Option<DryRun>,
 
-        /// Number of concurrent booking threads (default: 1, recommended: 3-5)
-        #[arg(long, default_value = "5")]
+        /// Number of concurrent booking threads once a slot appears
+        #[arg(long, default_value_t = api::DEFAULT_THREADS)]
         threads: usize,
 
-        /// Number of retry attempts per thread (default: 3)
-        #[arg(long, default_value = "5")]
+        /// Retries of each slot per booking thread after the first attempt (0 = a single attempt)
+        #[arg(long, default_value_t = api::DEFAULT_RETRIES)]
         retries: usize,
 
-        /// Poll interval in milliseconds when waiting for slots (default: 250ms)
+        /// Payment method to book with (default: the account's default card)
+        #[arg(long)]
+        payment_method_id: Option<u64>,
+
+        /// Skip slots whose cancellation/no-show fee exceeds this many dollars per person
+        #[arg(long)]
+        max_cancellation_fee: Option<f64>,
+
+        /// Skip slots that require a deposit or prepayment above this many dollars
+        #[arg(long)]
+        max_deposit: Option<f64>,
+
+        /// Log file path (default: ~/.resy-rust/logs/watch_<timestamp>.log)
+        #[arg(long)]
+        log_file: Option<String>,
+
+        #[command(flatten)]
+        hooks: hook::HookArgs,
+    },
+
+    /// List the payment methods on each account
+    PaymentMethods,
+
+    /// Check that credentials work and show how long each auth token stays valid
+    Ping {
+        /// Also fail if a token will have expired by this local time (YYYY-MM-DD HH:MM:SS),
+        /// e.g. the release time of a scheduled booking
+        #[arg(long)]
+        valid_at: Option<String>,
+    },
+
+    /// Run every check a scheduled booking depends on and print a pass/warn/fail report
+    Preflight {
+        /// Scheduled job to check (default: every pending or paused job)
+        job_id: Option<String>,
+
+        /// Warn if an auth token expires less than this many hours after the release
+        #[arg(long, default_value = "24")]
+        token_margin_hours: i64,
+
+        /// Warn if Resy's clock is further off than this many milliseconds
+        #[arg(long, default_value = "1000")]
+        max_clock_offset_ms: u64,
+
+        /// Warn if DNS resolution or connection setup takes longer than this many milliseconds
+        #[arg(long, default_value = "300")]
+        max_latency_ms: u64,
+    },
+
+    /// Measure DNS, connect, TLS and first-byte latency to the API and compare HTTP/1.1 with HTTP/2
+    Latency {
+        /// Samples per measurement
+        #[arg(long, default_value = "10")]
+        samples: usize,
+
+        /// Pause between samples in milliseconds
         #[arg(long, default_value = "250")]
-        poll_interval_ms: u64,
+        interval_ms: u64,
+    },
 
-        /// Maximum time to poll for slots in seconds (default: 30s)
+    /// Log in with email and password and store the auth token in the config file
+    Login {
+        /// Account email (default: the profile's email, or prompted)
+        #[arg(long)]
+        email: Option<String>,
+
+        /// Also store the password so rejected tokens are refreshed automatically
+        #[arg(long, default_value = "false")]
+        save_password: bool,
+    },
+
+    /// Manage notifications (webhooks and email)
+    Notify {
+        #[command(subcommand)]
+        action: NotifyCommand,
+    },
+
+    /// Schedule bookings for when reservations are released (stored in ~/.resy-rust/jobs)
+    Schedule {
+        #[command(subcommand)]
+        action: ScheduleCommand,
+    },
+
+    /// Run scheduled jobs on time, several at once, recording each outcome
+    Daemon {
+        /// Seconds before each release to warm up connections and sync clocks
         #[arg(long, default_value = "120")]
-        poll_timeout_secs: u64,
+        warmup_secs: u64,
 
-        /// Log file path (default: ~/.resy-rust/logs/<venue>_<timestamp>.log)
+        /// Serve the HTTP control API on this loopback address, e.g. 127.0.0.1:7878
+        #[arg(long)]
+        api_addr: Option<std::net::SocketAddr>,
+
+        /// Log file path (default: ~/.resy-rust/logs/daemon_<timestamp>.log)
         #[arg(long)]
         log_file: Option<String>,
     },
+
+    /// Manage stored credentials
+    Credentials {
+        #[command(subcommand)]
+        action: CredentialsCommand,
+    },
 }
 
-fn get_default_log_path(venue_id: &str) -> PathBuf {
-    let home = env::var("HOME").unwrap_or_else(|_| ".".to_string());
+#[derive(Parser, Debug)]
+#[allow(clippy::large_enum_variant)]
+enum ScheduleCommand {
+    /// Add a job that books as soon as reservations for its dates are released
+    Add {
+        /// Venue ID of the restaurant
+        #[arg(long)]
+        venue_id: String,
+
+        /// Party size for the reservation
+        #[arg(long)]
+        party_size: u32,
+
+        /// Reservation date (YYYY-MM-DD; several only with --strategy watch)
+        #[arg(long = "date", value_delimiter = ',', required = true)]
+        dates: Vec<String>,
+
+        /// Preferred reservation times (HH:MM:SS format, can specify multiple)
+        #[arg(long, value_delimiter = ',', required = true)]
+        times: Vec<String>,
+
+        /// Preferred reservation types (e.g., Indoor, Outdoor, can specify multiple)
+        #[arg(long, value_delimiter = ',')]
+        types: Option<Vec<String>>,
+
+        /// How to book once reservations are released
+        #[arg(long, value_enum, default_value = "competitive")]
+        strategy: Strategy,
+
+        /// Days in advance the venue releases reservations (default: the venue's lead time)
+        #[arg(long)]
+        lead_days: Option<u32>,
+
+        /// Local time of day reservations are released (HH:MM:SS; default: the earliest --times)
+        #[arg(long)]
+        release_time: Option<String>,
+
+        /// Exact local release time (YYYY-MM-DD HH:MM:SS), instead of computing it from the lead time
+        #[arg(long)]
+        release_at: Option<String>,
+
+        /// Number of concurrent booking threads
+        #[arg(long, default_value_t = api::DEFAULT_THREADS)]
+        threads: usize,
+
+        /// Retries of each slot per booking thread after the first attempt (0 = a single attempt)
+        #[arg(long, default_value_t = api::DEFAULT_RETRIES)]
+        retries: usize,
+
+        /// Poll interval in milliseconds when waiting for slots
+        #[arg(long, default_value_t = api::DEFAULT_POLL_INTERVAL_MS)]
+        poll_interval_ms: u64,
+
+        /// Maximum time to poll for slots in seconds
+        #[arg(long, default_value_t = api::DEFAULT_POLL_TIMEOUT_SECS)]
+        poll_timeout_secs: u64,
+
+        /// Seconds between checks with --strategy watch
+        #[arg(long, default_value = "60")]
+        interval_secs: u64,
+
+        /// Payment method to book with (default: the account's default card)
+        #[arg(long)]
+        payment_method_id: Option<u64>,
+
+        /// Skip slots whose cancellation/no-show fee exceeds this many dollars per person
+        #[arg(long)]
+        max_cancellation_fee: Option<f64>,
+
+        /// Skip slots that require a deposit or prepayment above this many dollars
+        #[arg(long)]
+        max_deposit: Option<f64>,
+
+        #[command(flatten)]
+        poll_schedule: poll::PollSchedule,
+    },
+
+    /// List scheduled jobs, soonest release first
+    List,
+
+    /// Show every setting of a scheduled job
+    Show {
+        /// Job ID (see `schedule list`)
+        id: String,
+    },
+
+    /// Delete a scheduled job (and its systemd units, if installed)
+    Remove {
+        /// Job ID (see `schedule list`)
+        id: String,
+    },
+
+    /// Run a job now: warm up, wait for its release and book (used by installed timers)
+    Run {
+        /// Job ID (see `schedule list`)
+        id: String,
+    },
+
+    /// Install a systemd --user service and timer for a job or for the daemon
+    Install {
+        /// Job ID (see `schedule list`)
+        #[arg(required_unless_present = "daemon", conflicts_with = "daemon")]
+        id: Option<String>,
+
+        /// Install units for `resy-rust daemon` instead of a single job
+        #[arg(long)]
+        daemon: bool,
+
+        /// Generate systemd user units (the only backend so far)
+        #[arg(long)]
+        systemd: bool,
+
+        /// Seconds before the release the job's timer fires
+        #[arg(long, default_value = "120")]
+        warmup_secs: u64,
+
+        /// Only write the unit files; don't reload systemd or enable the timer
+        #[arg(long, default_value = "false")]
+        no_enable: bool,
+    },
+
+    /// List installed resy-rust systemd timers
+    Units,
+
+    /// Disable and delete the systemd units for a job or for the daemon
+    Uninstall {
+        /// Job ID (see `schedule list`)
+        #[arg(required_unless_present = "daemon", conflicts_with = "daemon")]
+        id: Option<String>,
+
+        /// Remove the daemon's units
+        #[arg(long)]
+        daemon: bool,
+    },
+}
+
+#[derive(Parser, Debug)]
+enum NotifyCommand {
+    /// Send a sample event to every configured notification sink
+    Test {
+        /// Kind of event to send
+        #[arg(long, value_enum, default_value = "booked")]
+        event: EventKind,
+    },
+}
+
+#[derive(Parser, Debug)]
+enum CredentialsCommand {
+    /// Encrypt an auth token (read from stdin) with a passphrase for use with source = "encrypted"
+    Encrypt {
+        /// Output file (default: ~/.resy-rust/credentials/<profile>.enc)
+        #[arg(long)]
+        output: Option<PathBuf>,
+
+        /// Also store this API key in the encrypted file
+        #[arg(long)]
+        api_key: Option<String>,
+    },
+}
+
+/// Where log files go unless a command is given --log-file
+fn log_dir() -> PathBuf {
+    config::app_dir().join("logs")
+}
+
+fn get_default_log_path(prefix: &str) -> PathBuf {
     let timestamp = Local::now().format("%Y%m%d_%H%M%S");
-    PathBuf::from(home)
-        .join(".resy-rust")
-        .join("logs")
-        .join(format!("venue_{}_{}.log", venue_id, timestamp))
+    log_dir().join(format!("{}_{}.log", prefix, timestamp))
+}
+
+/// One client per account on the config's network settings, all sharing a rate
+/// limiter configured by its `[rate_limit]`. Each account gets its own circuit
+/// breaker, so one rejected token doesn't stop the others.
+fn build_clients(accounts: &[Credentials], notifier: &Notifier, config: &Config) -> Result<Vec<ResyClient>> {
+    let limiter = Arc::new(RateLimiter::new(config.rate_limit.clone()));
+    build_clients_sharing(accounts, notifier, config, &limiter)
+}
+
+/// Like `build_clients`, but drawing on an existing rate limiter
+fn build_clients_sharing(
+    accounts: &[Credentials],
+    notifier: &Notifier,
+    config: &Config,
+    limiter: &Arc<RateLimiter>,
+) -> Result<Vec<ResyClient>> {
+    accounts
+        .iter()
+        .cloned()
+        .map(|account| {
+            Ok(ResyClient::new(account)?
+                .with_network(&config.network)?
+                .with_notifier(notifier.clone())
+                .with_rate_limiter(Arc::clone(limiter))
+                .with_breaker(Arc::new(CircuitBreaker::default())))
+        })
+        .collect()
+}
+
+/// Parse a local "YYYY-MM-DD HH:MM:SS" timestamp
+fn parse_local_datetime(s: &str) -> Result<DateTime<Utc>> {
+    let naive = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S")
+        .context(format!("Invalid datetime '{}': expected YYYY-MM-DD HH:MM:SS", s))?;
+    let local = Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .context(format!("'{}' does not exist in the local timezone", s))?;
+    Ok(local.with_timezone(&Utc))
+}
+
+/// The single profile a command operates on: --profile, else default_profile, else "default"
+fn single_profile(profiles: &[String], config: &Config) -> Result<String> {
+    match profiles {
+        [] => Ok(config.default_profile.clone().unwrap_or_else(|| "default".to_string())),
+        [name] => Ok(name.clone()),
+        _ => anyhow::bail!("This command takes a single --profile"),
+    }
+}
+
+/// Warn about auth tokens close to expiry and refuse to start with an expired one
+fn check_token_expiry(accounts: &[Credentials], warn: impl Fn(&str)) -> Result<()> {
+    for account in accounts {
+        match TokenStatus::now(&account.auth_token) {
+            status @ TokenStatus::Expired { .. } => {
+                anyhow::bail!("Auth token for profile '{}' {}", account.profile, status.describe());
+            }
+            status @ TokenStatus::ExpiringSoon { .. } => {
+                warn(&format!("⚠️  Auth token for profile '{}' is {}", account.profile, status.describe()));
+            }
+            TokenStatus::Valid { .. } | TokenStatus::Unknown => {}
+        }
+    }
+    Ok(())
 }
 
 #[tokio::main]
-async fn main() -> Result<()> {
-    dotenv::dotenv().ok();
+async fn main() -> ExitCode {
+    match run().await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {:?}", e);
+            // A fatal auth error gets its own exit code so wrappers can tell it apart
+            if breaker::is_fatal(&e) {
+                ExitCode::from(breaker::FATAL_EXIT_CODE)
+            } else {
+                ExitCode::FAILURE
+            }
+        }
+    }
+}
 
-    let api_key = env::var("RESY_API_KEY")
-        .context("RESY_API_KEY not found in environment. Create a .env file with your credentials.")?;
-    let auth_token = env::var("RESY_AUTH_TOKEN")
-        .context("RESY_AUTH_TOKEN not found in environment. Create a .env file with your credentials.")?;
+async fn run() -> Result<()> {
+    dotenv::dotenv().ok();
 
     let cli = Cli::parse();
 
-    let client = ResyClient::new(api_key, auth_token)?;
+    let config_path = cli.config.clone().unwrap_or_else(config::default_config_path);
+    let mut config = Config::load(&config_path)?;
+    if let Some(rps) = cli.max_rps {
+        config.rate_limit.requests_per_sec = rps;
+    }
+    if let Some(budget) = cli.request_budget {
+        config.rate_limit.request_budget = Some(budget);
+    }
+    config.rate_limit.validate()?;
+    cli.retry.apply(&mut config.retry);
+    config.retry.validate()?;
 
     match cli.command {
         Commands::Book {
@@ -163,15 +640,50 @@ async fn main() -> Result<()> {
             poll_interval_ms,
             poll_timeout_secs,
             log_file,
+            payment_method_id,
+            max_cancellation_fee,
+            max_deposit,
+            release_at,
+            poll_schedule,
+            hooks,
+            job_file,
         } => {
+            if let Some(path) = job_file {
+                let mut batch = jobfile::load(&path)?;
+                if dry_run.is_some() {
+                    batch.set_dry_run(dry_run);
+                }
+                let profiles = if cli.profile.is_empty() { batch.profiles.clone() } else { cli.profile.clone() };
+                let accounts = credentials::resolve(&config, &profiles)?;
+                for job in &batch.jobs {
+                    api::check_payment_method_accounts(job.template.payment_method_id, accounts.len())
+                        .context(format!("Job {}", job.name))?;
+                }
+                let clients = build_clients(&accounts, &Notifier::default(), &config)?;
+
+                println!("📋 {} job(s) from {}", batch.jobs.len(), path.display());
+                check_token_expiry(&accounts, |line| println!("{}", line))?;
+                return jobfile::run(batch, &clients, &config).await;
+            }
+
+            // clap requires these unless --job-file is given
+            let (Some(venue_id), Some(party_size), Some(date)) = (venue_id, party_size, date) else {
+                anyhow::bail!("--venue-id, --party-size and --date are required");
+            };
+            let release_at = release_at.as_deref().map(parse_local_datetime).transpose()?;
+            poll_schedule.validate()?;
+            let accounts = credentials::resolve(&config, &cli.profile)?;
+            api::check_payment_method_accounts(payment_method_id, accounts.len())?;
             let types = types.unwrap_or_default();
             
             // Set up logging
             let log_path = log_file
                 .map(PathBuf::from)
-                .unwrap_or_else(|| get_default_log_path(&venue_id));
+                .unwrap_or_else(|| get_default_log_path(&format!("venue_{}", venue_id)));
             
             let logger = Logger::new(log_path.clone())?;
+            let notifier = Notifier::from_config(&config, hooks.sinks(), Some(logger.clone_handle()))?;
+            let clients = build_clients(&accounts, &notifier, &config)?;
             
             logger.log("═══════════════════════════════════════════════════════");
             logger.log("🚀 Starting Resy booking...");
@@ -188,25 +700,60 @@ async fn main() -> Result<()> {
                 logger.log(&format!("   Concurrent Threads: {}", threads));
             }
             if retries > 1 {
-                logger.log(&format!("   Retries per Thread: {}", retries));
+                logger.log(&format!("   Retries per Slot: {}", retries));
+            }
+            if let Some(id) = payment_method_id {
+                logger.log(&format!("   Payment Method: {}", id));
+            }
+            if let Some(max) = max_cancellation_fee {
+                logger.log(&format!("   Max Cancellation Fee: ${:.2}/person", max));
+            }
+            if let Some(max) = max_deposit {
+                logger.log(&format!("   Max Deposit: ${:.2}", max));
+            }
+            if accounts[0].profile != credentials::ENV_PROFILE {
+                let names: Vec<_> = accounts.iter().map(|a| a.profile.as_str()).collect();
+                logger.log(&format!("   Profiles: {}", names.join(", ")));
+            }
+            if let Some(release) = release_at {
+                logger.log(&format!("   Release: {}", release.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S")));
+            }
+            if dry_run == Some(DryRun::Deep) {
+                logger.log("   Dry Run: deep (stops before /3/book)");
             }
             logger.log(&format!("   Log File: {}", log_path.display()));
             logger.log("");
 
-            let result = client
-                .book_competitive(
-                    &venue_id,
-                    party_size,
-                    &date,
-                    &times,
-                    &types,
-                    dry_run,
-                    threads,
-                    retries,
-                    Duration::from_millis(poll_interval_ms),
-                    Duration::from_secs(poll_timeout_secs),
-                    logger.clone_handle(),
-                )
+            check_token_expiry(&accounts, |line| logger.log(line))?;
+
+            let params = BookingParams {
+                venue_id,
+                party_size,
+                day: date,
+                times,
+                types,
+                dry_run,
+                num_threads: threads,
+                num_retries: retries,
+                retry: config.retry.clone(),
+                poll_interval: Duration::from_millis(poll_interval_ms),
+                poll_timeout: Duration::from_secs(poll_timeout_secs),
+                poll_schedule,
+                release_at,
+                payment_method_id,
+                policy_limits: PolicyLimits {
+                    max_cancellation_fee,
+                    max_deposit,
+                },
+            };
+
+            if let Some(release) = release_at {
+                poll::wait_for_release(release, &poll_schedule, &logger.clone_handle()).await;
+            }
+            let clients = dns::pin_clients(clients, &config.network, &logger.clone_handle()).await;
+
+            let result = clients[0]
+                .book_competitive(&params, &clients, logger.clone_handle())
                 .await;
 
             match &result {
@@ -222,8 +769,529 @@ async fn main() -> Result<()> {
                 }
             }
 
+            notifier.flush().await;
             result?;
         }
+
+        Commands::Ping { valid_at } => {
+            let valid_at = valid_at.as_deref().map(parse_local_datetime).transpose()?;
+            let accounts = credentials::resolve(&config, &cli.profile)?;
+            let mut failed = false;
+
+            for account in accounts {
+                let profile = account.profile.clone();
+                let client = ResyClient::new(account)?.with_network(&config.network)?;
+
+                match client.fetch_user().await {
+                    Ok(user) => {
+                        let name = [user.first_name, user.last_name]
+                            .into_iter()
+                            .flatten()
+                            .collect::<Vec<_>>()
+                            .join(" ");
+                        println!("✅ {}: authenticated as {}", profile, name);
+                        if let Some(email) = user.em_address {
+                            println!("   Email: {}", email);
+                        }
+                    }
+                    Err(e) => {
+                        failed = true;
+                        println!("❌ {}: {}", profile, e);
+                    }
+                }
+
+                let status = client.token_status();
+                println!("   Token: {}", status.describe());
+
+                if let Some(instant) = valid_at {
+                    if status.expires_at().is_some_and(|expires_at| expires_at <= instant) {
+                        failed = true;
+                        println!(
+                            "   ❌ Token will have expired by {}",
+                            instant.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S")
+                        );
+                    }
+                }
+            }
+
+            if failed {
+                anyhow::bail!("Ping failed. Refresh your auth token and try again.");
+            }
+        }
+
+        Commands::Watch {
+            targets,
+            party_size,
+            times,
+            types,
+            interval_secs,
+            jitter_pct,
+            duration_hours,
+            alert_only,
+            dry_run,
+            threads,
+            retries,
+            payment_method_id,
+            max_cancellation_fee,
+            max_deposit,
+            log_file,
+            hooks,
+        } => {
+            let accounts = credentials::resolve(&config, &cli.profile)?;
+            api::check_payment_method_accounts(payment_method_id, accounts.len())?;
+            let types = types.unwrap_or_default();
+
+            let log_path = log_file
+                .map(PathBuf::from)
+                .unwrap_or_else(|| get_default_log_path("watch"));
+            let logger = Logger::new(log_path.clone())?;
+            let notifier = Notifier::from_config(&config, hooks.sinks(), Some(logger.clone_handle()))?;
+            let clients = build_clients(&accounts, &notifier, &config)?;
+
+            logger.log("═══════════════════════════════════════════════════════");
+            logger.log("👀 Starting Resy watch...");
+            for target in &targets {
+                logger.log(&format!("   Target: venue {} on {}", target.venue_id, target.day));
+            }
+            logger.log(&format!("   Party Size: {}", party_size));
+            if !times.is_empty() {
+                logger.log(&format!("   Times: {}", times.join(", ")));
+            }
+            if !types.is_empty() {
+                logger.log(&format!("   Types: {}", types.join(", ")));
+            }
+            logger.log(&format!("   Interval: {}s ±{}%", interval_secs, jitter_pct));
+            if let Some(hours) = duration_hours {
+                logger.log(&format!("   Duration: {}h", hours));
+            }
+            let mode = match (alert_only, dry_run) {
+                (true, _) => "alert only",
+                (false, Some(DryRun::Deep)) => "deep dry run",
+                (false, Some(DryRun::Shallow)) => "dry run",
+                (false, None) => "book",
+            };
+            logger.log(&format!("   Mode: {}", mode));
+            logger.log(&format!("   Log File: {}", log_path.display()));
+            logger.log("");
+
+            check_token_expiry(&accounts, |line| logger.log(line))?;
+
+            let options = watch::WatchOptions {
+                targets,
+                template: BookingParams {
+                    venue_id: String::new(),
+                    party_size,
+                    day: String::new(),
+                    times,
+                    types,
+                    dry_run,
+                    num_threads: threads,
+                    num_retries: retries,
+                    retry: config.retry.clone(),
+                    poll_interval: Duration::from_secs(interval_secs),
+                    poll_timeout: Duration::ZERO,
+                    poll_schedule: Default::default(),
+                    release_at: None,
+                    payment_method_id,
+                    policy_limits: PolicyLimits {
+                        max_cancellation_fee,
+                        max_deposit,
+                    },
+                },
+                interval: Duration::from_secs(interval_secs.max(1)),
+                jitter: f64::from(jitter_pct) / 100.0,
+                alert_only,
+                duration: duration_hours.map(|hours| Duration::from_secs_f64(hours.max(0.0) * 3600.0)),
+            };
+
+            let result = watch::watch(&clients, options, logger.clone_handle()).await;
+            if let Err(e) = &result {
+                logger.log(&format!("❌ Watch failed: {}", e));
+            }
+            notifier.flush().await;
+            result?;
+        }
+
+        Commands::Preflight { job_id, token_margin_hours, max_clock_offset_ms, max_latency_ms } => {
+            let thresholds = preflight::Thresholds {
+                token_margin: chrono::Duration::hours(token_margin_hours),
+                max_clock_offset: Duration::from_millis(max_clock_offset_ms),
+                max_latency: Duration::from_millis(max_latency_ms),
+            };
+            let store = JobStore::open_default();
+            let jobs = match job_id {
+                Some(id) => vec![store.get(&id)?],
+                None => store
+                    .list()?
+                    .into_iter()
+                    .filter(|job| matches!(job.status, JobStatus::Pending | JobStatus::Paused))
+                    .collect(),
+            };
+
+            // Each profile is loaded once, however many jobs use it
+            let credentials = CredentialCache::default();
+            let mut report = preflight::Report::default();
+            preflight::check_environment(&config.network, &log_dir(), &thresholds, &mut report).await;
+
+            if jobs.is_empty() {
+                println!();
+                println!("🛫 Accounts (no scheduled jobs to check)");
+                preflight::check_accounts(&credentials, &config, &cli.profile, Utc::now(), &thresholds, &mut report).await;
+            }
+            for job in &jobs {
+                preflight::check_job(job, &credentials, &config, &thresholds, &mut report).await;
+            }
+            report.finish()?;
+        }
+
+        Commands::Latency { samples, interval_ms } => {
+            if samples == 0 {
+                anyhow::bail!("--samples must be at least 1");
+            }
+            let limiter = RateLimiter::new(config.rate_limit.clone());
+            let options = latency::LatencyOptions {
+                samples,
+                interval: Duration::from_millis(interval_ms),
+            };
+            latency::run(&config.network, &limiter, options).await?;
+        }
+
+        Commands::PaymentMethods => {
+            for account in credentials::resolve(&config, &cli.profile)? {
+                let profile = account.profile.clone();
+                let user = ResyClient::new(account)?.with_network(&config.network)?.fetch_user().await?;
+                let methods = user.payment_methods.unwrap_or_default();
+
+                println!("💳 {}:", profile);
+                if methods.is_empty() {
+                    println!("   No payment methods on file");
+                }
+                for method in methods {
+                    let mut flags = Vec::new();
+                    if method.is_default {
+                        flags.push("default");
+                    }
+                    if method.is_expired() {
+                        flags.push("expired");
+                    }
+                    let flags = if flags.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" ({})", flags.join(", "))
+                    };
+                    println!("   {}  {}{}", method.id, method.describe(), flags);
+                }
+            }
+        }
+
+        Commands::Login { email, save_password } => {
+            let profile = single_profile(&cli.profile, &config)?;
+            let existing = config.profiles.get(&profile).cloned().unwrap_or_default();
+
+            let email = match email.or(existing.email.clone()) {
+                Some(email) => email,
+                None => {
+                    eprint!("Email: ");
+                    std::io::stderr().flush()?;
+                    let mut line = String::new();
+                    std::io::stdin().read_line(&mut line)?;
+                    line.trim().to_string()
+                }
+            };
+            let password = match std::env::var(credentials::PASSWORD_ENV) {
+                Ok(password) => password,
+                Err(_) => rpassword::prompt_password("Password: ")?,
+            };
+
+            let client = ResyClient::new(Credentials {
+                profile: profile.clone(),
+                api_key: credentials::api_key_for(&config, &profile)?,
+                auth_token: String::new(),
+                login: None,
+            })?
+            .with_network(&config.network)?;
+            let response = client.password_login(&email, &password).await?;
+
+            let mut values = vec![("auth_token", response.token.as_str()), ("email", email.as_str())];
+            if save_password {
+                values.push(("password", password.as_str()));
+            }
+            Config::update_profile(&config_path, &profile, &values)?;
+
+            let name = [response.first_name, response.last_name]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>()
+                .join(" ");
+            println!("✅ Logged in as {}", name);
+            println!("   Token: {}", TokenStatus::now(&response.token).describe());
+            println!("   Saved to [profiles.{}] in {}", profile, config_path.display());
+            if existing.source != CredentialSource::Config {
+                println!("   ⚠️  This profile loads its token from another source; set source = \"config\" to use it");
+            }
+        }
+
+        Commands::Notify { action } => match action {
+            NotifyCommand::Test { event } => {
+                let notifier = Notifier::from_config(&config, Vec::new(), None)?;
+                if notifier.sinks().is_empty() {
+                    anyhow::bail!("No notification sinks configured in {}", config_path.display());
+                }
+
+                let results = notifier.deliver_now(&notify::Event::sample(event)).await;
+                let mut failed = false;
+                for (name, result) in results {
+                    match result {
+                        Ok(()) => println!("✅ {}", name),
+                        Err(e) => {
+                            failed = true;
+                            println!("❌ {}: {:#}", name, e);
+                        }
+                    }
+                }
+                if failed {
+                    anyhow::bail!("Some notifications failed");
+                }
+            }
+        },
+
+        Commands::Schedule { action } => match action {
+            ScheduleCommand::Add {
+                venue_id,
+                party_size,
+                dates,
+                times,
+                types,
+                strategy,
+                lead_days,
+                release_time,
+                release_at,
+                threads,
+                retries,
+                poll_interval_ms,
+                poll_timeout_secs,
+                interval_secs,
+                payment_method_id,
+                max_cancellation_fee,
+                max_deposit,
+                poll_schedule,
+            } => {
+                poll_schedule.validate()?;
+                let spec = schedule::JobSpec {
+                    venue_id,
+                    party_size,
+                    dates,
+                    times,
+                    types: types.unwrap_or_default(),
+                    strategy,
+                    lead_days,
+                    release_time,
+                    release_at,
+                    profiles: cli.profile.clone(),
+                    threads,
+                    retries,
+                    poll_interval_ms,
+                    poll_timeout_secs,
+                    watch_interval_secs: interval_secs,
+                    payment_method_id,
+                    max_cancellation_fee,
+                    max_deposit,
+                    poll_schedule,
+                };
+                let job = schedule::create_job(spec, &config, &CredentialCache::default(), &|warning| println!("⚠️  {}", warning)).await?;
+                let path = JobStore::open_default().save(&job)?;
+
+                println!("📅 Scheduled {} for {} on {}", job.id, job.venue_label(), job.dates.join(", "));
+                println!("   Release: {} (in {})", job.release_local(),
+                    token::format_remaining(job.release_at - Utc::now()));
+                println!("   Saved to {}", path.display());
+            }
+
+            ScheduleCommand::List => {
+                let jobs = JobStore::open_default().list()?;
+                if jobs.is_empty() {
+                    println!("No scheduled jobs");
+                }
+                for job in jobs {
+                    let countdown = if job.release_at > Utc::now() {
+                        format!("in {}", token::format_remaining(job.release_at - Utc::now()))
+                    } else {
+                        "released".to_string()
+                    };
+                    println!("{}  {} ({})  {}  [{}]", job.id, job.release_local(), countdown,
+                        job.venue_label(), job.status.as_str());
+                    println!("   {} · party of {} · {} · {}", job.dates.join(", "), job.party_size,
+                        job.times.join(", "), job.strategy.as_str());
+                }
+            }
+
+            ScheduleCommand::Show { id } => {
+                let job = JobStore::open_default().get(&id)?;
+                println!("📅 Job {}", job.id);
+                println!("   Venue: {} ({})", job.venue_label(), job.venue_id);
+                println!("   Dates: {}", job.dates.join(", "));
+                println!("   Party Size: {}", job.party_size);
+                println!("   Times: {}", job.times.join(", "));
+                println!("   Types: {}", if job.types.is_empty() { "Any".to_string() } else { job.types.join(", ") });
+                println!("   Strategy: {}", job.strategy.as_str());
+                println!("   Release: {}", job.release_local());
+                println!("   Profiles: {}", if job.profiles.is_empty() { "default".to_string() } else { job.profiles.join(", ") });
+                println!("   Threads: {}, Retries: {}", job.threads, job.retries);
+                println!("   Poll: {} (for {}s)", job.poll_schedule.describe(Duration::from_millis(job.poll_interval_ms)),
+                    job.poll_timeout_secs);
+                if job.strategy == Strategy::Watch {
+                    println!("   Watch Interval: {}s", job.watch_interval_secs);
+                }
+                if let Some(id) = job.payment_method_id {
+                    println!("   Payment Method: {}", id);
+                }
+                if let Some(max) = job.max_cancellation_fee {
+                    println!("   Max Cancellation Fee: ${:.2}/person", max);
+                }
+                if let Some(max) = job.max_deposit {
+                    println!("   Max Deposit: ${:.2}", max);
+                }
+                println!("   Created: {}", job.created_at.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S"));
+                println!("   Status: {}", job.status.as_str());
+                if let Some(outcome) = &job.outcome {
+                    println!("   Finished: {}", outcome.finished_at.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S"));
+                    println!("   Outcome: {}", outcome.message);
+                    if let Some(id) = outcome.reservation_id {
+                        println!("   Reservation ID: {}", id);
+                    }
+                    if let Some(path) = &outcome.log_file {
+                        println!("   Log: {}", path.display());
+                    }
+                }
+            }
+
+            ScheduleCommand::Remove { id } => {
+                let job = JobStore::open_default().remove(&id)?;
+                println!("🗑️  Removed {} ({} on {})", job.id, job.venue_label(), job.dates.join(", "));
+
+                let unit = systemd::job_unit_name(&job.id);
+                if systemd::is_installed(&unit) {
+                    systemd::uninstall(&unit)?;
+                    println!("   Removed systemd units {}", unit);
+                }
+            }
+
+            ScheduleCommand::Run { id } => {
+                let store = JobStore::open_default();
+                let job = store.get(&id)?;
+                if job.status == JobStatus::Booked {
+                    println!("✅ {} already booked", job.id);
+                    return Ok(());
+                }
+                let Some(lock) = store.try_lock(&job.id)? else {
+                    anyhow::bail!("Job {} is already being run by another process", job.id);
+                };
+
+                let log_path = get_default_log_path(&format!("job_{}", job.id));
+                let logger = Logger::new(log_path.clone())?;
+                let stats = Arc::new(api::EngineStats::default());
+                let resources = daemon::JobResources {
+                    credentials: Arc::new(CredentialCache::default()),
+                    limiter: Arc::new(RateLimiter::new(config.rate_limit.clone())),
+                };
+                let status = daemon::run_job(job, lock, config_path.clone(), log_path, stats, resources, logger.clone_handle()).await;
+                if status != JobStatus::Booked {
+                    anyhow::bail!("Job {} {}", id, status.as_str());
+                }
+            }
+
+            ScheduleCommand::Install { id, daemon, systemd, warmup_secs, no_enable } => {
+                if !systemd {
+                    anyhow::bail!("Pass --systemd (systemd user units are the only supported scheduler)");
+                }
+
+                let invocation = systemd::Invocation::current(&config_path)?;
+                let units = match id {
+                    Some(id) if !daemon => {
+                        let job = JobStore::open_default().get(&id)?;
+                        if job.release_at <= Utc::now() {
+                            anyhow::bail!("Job {} was released at {}", job.id, job.release_local());
+                        }
+                        systemd::job_units(&job, &invocation, Duration::from_secs(warmup_secs))?
+                    }
+                    _ => systemd::daemon_units(&invocation),
+                };
+
+                for path in systemd::install(&units, !no_enable)? {
+                    println!("📝 Wrote {}", path.display());
+                }
+                if no_enable {
+                    println!("   Enable with: systemctl --user daemon-reload && systemctl --user enable --now {}.timer", units.name);
+                } else {
+                    println!("⏰ Enabled {}.timer", units.name);
+                }
+            }
+
+            ScheduleCommand::Units => {
+                let units = systemd::list()?;
+                if units.is_empty() {
+                    println!("No resy-rust units in {}", systemd::unit_dir().display());
+                }
+                for unit in units {
+                    println!("{}  {}", unit.name, unit.trigger);
+                }
+            }
+
+            ScheduleCommand::Uninstall { id, daemon } => {
+                let name = match id {
+                    Some(id) if !daemon => systemd::job_unit_name(&id),
+                    _ => systemd::daemon_unit_name(),
+                };
+                systemd::uninstall(&name)?;
+                println!("🗑️  Removed {}.service and {}.timer", name, name);
+            }
+        },
+
+        Commands::Daemon { warmup_secs, api_addr, log_file } => {
+            let log_path = log_file
+                .map(PathBuf::from)
+                .unwrap_or_else(|| get_default_log_path("daemon"));
+            let logger = Logger::new(log_path.clone())?;
+            logger.log(&format!("📝 Log File: {}", log_path.display()));
+
+            let mut api_token = String::new();
+            if api_addr.is_some() {
+                api_token = control::load_or_create_token()?;
+                if std::env::var(control::TOKEN_ENV).is_err() {
+                    logger.log(&format!("🔑 Control API token: {}", control::token_path().display()));
+                }
+            }
+
+            let options = daemon::DaemonOptions {
+                config_path: config_path.clone(),
+                log_path,
+                warmup: Duration::from_secs(warmup_secs),
+                api_addr,
+                api_token,
+                rate_limit: config.rate_limit.clone(),
+            };
+            daemon::run(options, logger.clone_handle()).await?;
+        }
+
+        Commands::Credentials { action } => match action {
+            CredentialsCommand::Encrypt { output, api_key } => {
+                let profile = single_profile(&cli.profile, &config)?;
+                let path = output.unwrap_or_else(|| credentials::default_encrypted_path(&profile));
+
+                let auth_token = credentials::read_token_from_stdin("Auth token: ")?;
+                let passphrase = credentials::read_passphrase("New passphrase: ")?;
+                if std::env::var(credentials::PASSPHRASE_ENV).is_err()
+                    && rpassword::prompt_password("Confirm passphrase: ")? != passphrase
+                {
+                    anyhow::bail!("Passphrases do not match");
+                }
+
+                vault::encrypt(&vault::VaultContents { api_key, auth_token }, &passphrase, &path)?;
+                println!("🔐 Encrypted credentials written to {}", path.display());
+                println!("   Add to [profiles.{}] in {}:", profile, config_path.display());
+                println!("   source = \"encrypted\"");
+            }
+        },
     }
 
     Ok(())
@@ -0,0 +1,444 @@
+use anyhow::Result;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::task::JoinHandle;
+
+use crate::api::{BookedSlot, BookingParams, DryRun};
+use crate::config::Config;
+use crate::email::EmailSink;
+use crate::hook::HookSink;
+use crate::types::Slot;
+use crate::webhook::WebhookSink;
+use crate::LoggerHandle;
+
+/// Things the engine reports to notification sinks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    SlotsFound,
+    Booked,
+    Failed,
+    AuthError,
+}
+
+impl EventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::SlotsFound => "slots_found",
+            Self::Booked => "booked",
+            Self::Failed => "failed",
+            Self::AuthError => "auth_error",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SlotInfo {
+    /// "YYYY-MM-DD HH:MM:SS"
+    pub start: String,
+    #[serde(rename = "type")]
+    pub slot_type: String,
+}
+
+impl From<&Slot> for SlotInfo {
+    fn from(slot: &Slot) -> Self {
+        Self {
+            start: slot.date.start.clone(),
+            slot_type: slot.config.slot_type.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Confirmation {
+    pub reservation_id: Option<u64>,
+    pub resy_token: Option<String>,
+}
+
+/// A booking event, serialized as the default webhook payload
+#[derive(Debug, Clone, Serialize)]
+pub struct Event {
+    pub event: EventKind,
+    /// RFC 3339 UTC timestamp
+    pub timestamp: String,
+    /// One-line human-readable description
+    pub summary: String,
+    pub profile: Option<String>,
+    pub venue_id: Option<String>,
+    pub venue_name: Option<String>,
+    pub date: Option<String>,
+    pub party_size: Option<u32>,
+    /// Slots found, or the slot that was booked
+    pub slots: Vec<SlotInfo>,
+    pub confirmation: Option<Confirmation>,
+    pub error: Option<String>,
+}
+
+impl Event {
+    fn new(event: EventKind, summary: String) -> Self {
+        Self {
+            event,
+            timestamp: Utc::now().to_rfc3339(),
+            summary,
+            profile: None,
+            venue_id: None,
+            venue_name: None,
+            date: None,
+            party_size: None,
+            slots: Vec::new(),
+            confirmation: None,
+            error: None,
+        }
+    }
+
+    fn for_booking(mut self, params: &BookingParams, venue_name: &str) -> Self {
+        self.venue_id = Some(params.venue_id.clone());
+        self.venue_name = Some(venue_name.to_string());
+        self.date = Some(params.day.clone());
+        self.party_size = Some(params.party_size);
+        self
+    }
+
+    pub fn slots_found(params: &BookingParams, venue_name: &str, slots: &[Slot]) -> Self {
+        let summary = format!(
+            "{} matching slot(s) at {} on {} for {}: {}",
+            slots.len(),
+            venue_name,
+            params.day,
+            params.party_size,
+            slots.iter().map(|s| s.date.start.as_str()).collect::<Vec<_>>().join(", ")
+        );
+        let mut event = Self::new(EventKind::SlotsFound, summary).for_booking(params, venue_name);
+        event.slots = slots.iter().map(SlotInfo::from).collect();
+        event
+    }
+
+    pub fn booked(params: &BookingParams, venue_name: &str, booked: &BookedSlot) -> Self {
+        let summary = format!(
+            "Booked {} at {} ({}) for {}",
+            venue_name, booked.slot.date.start, booked.slot.config.slot_type, params.party_size
+        );
+        let mut event = Self::new(EventKind::Booked, summary).for_booking(params, venue_name);
+        event.profile = Some(booked.profile.clone());
+        event.slots = vec![SlotInfo::from(&booked.slot)];
+        event.confirmation = Some(Confirmation {
+            reservation_id: booked.confirmation.reservation_id,
+            resy_token: booked.confirmation.resy_token.clone(),
+        });
+        event
+    }
+
+    pub fn failed(params: &BookingParams, venue_name: &str, error: &anyhow::Error) -> Self {
+        let summary = format!("Failed to book {} on {}: {}", venue_name, params.day, error);
+        let mut event = Self::new(EventKind::Failed, summary).for_booking(params, venue_name);
+        event.error = Some(error.to_string());
+        event
+    }
+
+    pub fn auth_error(profile: &str, error: &anyhow::Error) -> Self {
+        let summary = format!("Resy rejected the credentials for profile '{}': {}", profile, error);
+        let mut event = Self::new(EventKind::AuthError, summary);
+        event.profile = Some(profile.to_string());
+        event.error = Some(error.to_string());
+        event
+    }
+
+    /// An example event for `notify test`
+    pub fn sample(kind: EventKind) -> Self {
+        let params = BookingParams {
+            venue_id: "12345".to_string(),
+            party_size: 2,
+            day: Utc::now().format("%Y-%m-%d").to_string(),
+            times: Vec::new(),
+            types: Vec::new(),
+            dry_run: Some(DryRun::Shallow),
+            num_threads: 1,
+            num_retries: 1,
+            retry: Default::default(),
+            poll_interval: std::time::Duration::ZERO,
+            poll_timeout: std::time::Duration::ZERO,
+            poll_schedule: Default::default(),
+            release_at: None,
+            payment_method_id: None,
+            policy_limits: Default::default(),
+        };
+        let venue_name = "Test Venue (resy-rust notify test)";
+        let slot = Slot {
+            date: crate::types::SlotDate {
+                start: format!("{} 19:00:00", params.day),
+            },
+            config: crate::types::SlotConfig {
+                slot_type: "Dining Room".to_string(),
+                token: String::new(),
+            },
+        };
+        let error = anyhow::anyhow!("This is a test notification");
+
+        match kind {
+            EventKind::SlotsFound => Self::slots_found(&params, venue_name, &[slot]),
+            EventKind::Booked => Self::booked(
+                &params,
+                venue_name,
+                &BookedSlot {
+                    slot,
+                    profile: "test".to_string(),
+                    confirmation: crate::types::BookResponse {
+                        resy_token: None,
+                        reservation_id: Some(123456789),
+                    },
+                },
+            ),
+            EventKind::Failed => Self::failed(&params, venue_name, &error),
+            EventKind::AuthError => Self::auth_error("test", &error),
+        }
+    }
+
+    /// Flat string values available to payload templates as {{name}}
+    pub fn template_value(&self, name: &str) -> Option<String> {
+        let first_slot = self.slots.first();
+        Some(match name {
+            "event" => self.event.as_str().to_string(),
+            "timestamp" => self.timestamp.clone(),
+            "summary" => self.summary.clone(),
+            "profile" => self.profile.clone().unwrap_or_default(),
+            "venue_id" => self.venue_id.clone().unwrap_or_default(),
+            "venue_name" => self.venue_name.clone().unwrap_or_default(),
+            "date" => self.date.clone().unwrap_or_default(),
+            "party_size" => self.party_size.map(|p| p.to_string()).unwrap_or_default(),
+            "slot_time" => first_slot.map(|s| s.start.clone()).unwrap_or_default(),
+            "slot_type" => first_slot.map(|s| s.slot_type.clone()).unwrap_or_default(),
+            "slots" => self
+                .slots
+                .iter()
+                .map(|s| format!("{} ({})", s.start, s.slot_type))
+                .collect::<Vec<_>>()
+                .join(", "),
+            "reservation_id" => self
+                .confirmation
+                .as_ref()
+                .and_then(|c| c.reservation_id)
+                .map(|id| id.to_string())
+                .unwrap_or_default(),
+            "resy_token" => self
+                .confirmation
+                .as_ref()
+                .and_then(|c| c.resy_token.clone())
+                .unwrap_or_default(),
+            "error" => self.error.clone().unwrap_or_default(),
+            _ => return None,
+        })
+    }
+}
+
+/// Render a payload template. `{{name}}` is replaced with the JSON-escaped
+/// value (no surrounding quotes), so templates like `{"text": "{{summary}}"}`
+/// stay valid JSON. `{{json}}` inserts the whole event as a JSON object.
+pub fn render_template(template: &str, event: &Event) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            output.push_str(&rest[start..]);
+            return output;
+        };
+
+        let name = after[..end].trim();
+        if name == "json" {
+            output.push_str(&serde_json::to_string(event).unwrap_or_default());
+        } else if let Some(value) = event.template_value(name) {
+            let quoted = serde_json::to_string(&value).unwrap_or_default();
+            output.push_str(&quoted[1..quoted.len() - 1]);
+        } else {
+            // Leave unknown placeholders untouched
+            output.push_str(&rest[start..start + 2 + end + 2]);
+        }
+        rest = &after[end + 2..];
+    }
+
+    output.push_str(rest);
+    output
+}
+
+/// A configured notification destination
+pub enum Sink {
+    Webhook(WebhookSink),
+    Email(Box<EmailSink>),
+    Hook(HookSink),
+}
+
+impl Sink {
+    pub fn name(&self) -> String {
+        match self {
+            Self::Webhook(sink) => sink.name(),
+            Self::Email(sink) => sink.name(),
+            Self::Hook(sink) => sink.name(),
+        }
+    }
+
+    fn accepts(&self, kind: EventKind) -> bool {
+        match self {
+            Self::Webhook(sink) => sink.accepts(kind),
+            Self::Email(sink) => sink.accepts(kind),
+            Self::Hook(sink) => sink.accepts(kind),
+        }
+    }
+
+    async fn deliver(&self, event: &Event) -> Result<()> {
+        match self {
+            Self::Webhook(sink) => sink.deliver(event).await,
+            Self::Email(sink) => sink.deliver(event).await,
+            Self::Hook(sink) => sink.deliver(event).await,
+        }
+    }
+}
+
+struct Inner {
+    sinks: Vec<Sink>,
+    logger: Option<LoggerHandle>,
+    pending: Mutex<Vec<JoinHandle<()>>>,
+    auth_error_sent: AtomicBool,
+}
+
+/// Fans booking events out to every configured sink in the background, so
+/// slow or failing destinations never hold up the booking path
+#[derive(Clone)]
+pub struct Notifier {
+    inner: Arc<Inner>,
+}
+
+impl Default for Notifier {
+    fn default() -> Self {
+        Self::new(Vec::new(), None)
+    }
+}
+
+impl Notifier {
+    pub fn new(sinks: Vec<Sink>, logger: Option<LoggerHandle>) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                sinks,
+                logger,
+                pending: Mutex::new(Vec::new()),
+                auth_error_sent: AtomicBool::new(false),
+            }),
+        }
+    }
+
+    /// Build sinks from the config file, plus any given on the command line
+    pub fn from_config(config: &Config, extra: Vec<Sink>, logger: Option<LoggerHandle>) -> Result<Self> {
+        let mut sinks = extra;
+        for webhook in &config.webhooks {
+            sinks.push(Sink::Webhook(WebhookSink::new(webhook.clone())?));
+        }
+        if let Some(smtp) = &config.smtp {
+            sinks.push(Sink::Email(Box::new(EmailSink::new(smtp.clone())?)));
+        }
+        Ok(Self::new(sinks, logger))
+    }
+
+    pub fn sinks(&self) -> &[Sink] {
+        &self.inner.sinks
+    }
+
+    /// Log to the run's log file, or stderr when there isn't one
+    pub fn log(&self, message: &str) {
+        match &self.inner.logger {
+            Some(logger) => logger.log(message),
+            None => eprintln!("{}", message),
+        }
+    }
+
+    /// Queue an event for delivery to every sink that wants it
+    pub fn emit(&self, event: Event) {
+        // Report a rejected token once per run rather than from every worker
+        if event.event == EventKind::AuthError && self.inner.auth_error_sent.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let event = Arc::new(event);
+        let mut pending = self.inner.pending.lock().unwrap();
+        pending.retain(|handle| !handle.is_finished());
+
+        for index in 0..self.inner.sinks.len() {
+            if !self.inner.sinks[index].accepts(event.event) {
+                continue;
+            }
+            let notifier = self.clone();
+            let event = Arc::clone(&event);
+            pending.push(tokio::spawn(async move {
+                let sink = &notifier.inner.sinks[index];
+                if let Err(e) = sink.deliver(&event).await {
+                    notifier.log(&format!("⚠️  Notification to {} failed: {:#}", sink.name(), e));
+                }
+            }));
+        }
+    }
+
+    /// Deliver an event to every sink that wants it immediately, returning each sink's result
+    pub async fn deliver_now(&self, event: &Event) -> Vec<(String, Result<()>)> {
+        let mut results = Vec::new();
+        for sink in self.inner.sinks.iter().filter(|sink| sink.accepts(event.event)) {
+            results.push((sink.name(), sink.deliver(event).await));
+        }
+        results
+    }
+
+    /// Wait for queued notifications to finish before the process exits
+    pub async fn flush(&self) {
+        let handles: Vec<_> = self.inner.pending.lock().unwrap().drain(..).collect();
+        for handle in handles {
+            let _ = handle.await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_template_substitutes_placeholders() {
+        let event = Event::sample(EventKind::Booked);
+        let rendered = render_template("{{event}} {{ venue_id }} #{{reservation_id}} ({{party_size}})", &event);
+        assert_eq!(rendered, "booked 12345 #123456789 (2)");
+    }
+
+    #[test]
+    fn render_template_escapes_values_as_json() {
+        let error = anyhow::anyhow!("line one\nsaid \"no\" \\ twice");
+        let event = Event::auth_error("test", &error);
+        let rendered = render_template(r#"{"text": "{{error}}"}"#, &event);
+
+        assert_eq!(rendered, r#"{"text": "line one\nsaid \"no\" \\ twice"}"#);
+        let parsed: serde_json::Value = serde_json::from_str(&rendered).unwrap();
+        assert_eq!(parsed["text"], "line one\nsaid \"no\" \\ twice");
+    }
+
+    #[test]
+    fn render_template_inserts_whole_event_for_json() {
+        let event = Event::sample(EventKind::Failed);
+        let rendered = render_template(r#"{"payload": {{json}}}"#, &event);
+
+        let parsed: serde_json::Value = serde_json::from_str(&rendered).unwrap();
+        assert_eq!(parsed["payload"]["event"], "failed");
+        assert_eq!(parsed["payload"]["error"], "This is a test notification");
+    }
+
+    #[test]
+    fn render_template_leaves_unknown_and_unclosed_placeholders() {
+        let event = Event::sample(EventKind::Failed);
+        assert_eq!(render_template("{{nope}} {{event}}", &event), "{{nope}} failed");
+        assert_eq!(render_template("{{event}} {{event", &event), "failed {{event");
+    }
+
+    #[test]
+    fn render_template_blanks_missing_optional_values() {
+        let event = Event::sample(EventKind::AuthError);
+        assert_eq!(render_template("[{{venue_name}}][{{slot_time}}]", &event), "[][]");
+    }
+}
@@ -0,0 +1,111 @@
+use std::fmt;
+
+use crate::types::DetailsResponse;
+
+/// Upper bounds on what a booking may commit us to, in dollars
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PolicyLimits {
+    /// Per-person cancellation / no-show fee
+    pub max_cancellation_fee: Option<f64>,
+    /// Deposit or prepayment charged at booking time
+    pub max_deposit: Option<f64>,
+}
+
+/// Cancellation and payment terms of a slot, taken from /3/details
+#[derive(Debug, Clone, Default)]
+pub struct BookingPolicy {
+    pub cancellation_fee: Option<f64>,
+    pub cancellation_cut_off: Option<String>,
+    pub deposit: Option<f64>,
+    pub payment_type: Option<String>,
+    pub policy_text: Vec<String>,
+}
+
+impl BookingPolicy {
+    pub fn from_details(details: &DetailsResponse) -> Self {
+        let fee = details.cancellation.as_ref().and_then(|c| c.fee.as_ref());
+        let payment = details.payment.as_ref();
+
+        Self {
+            cancellation_fee: fee.and_then(|f| f.amount).filter(|amount| *amount > 0.0),
+            cancellation_cut_off: fee.and_then(|f| f.date_cut_off.clone()),
+            deposit: payment
+                .and_then(|p| p.amounts.as_ref())
+                .and_then(|a| a.reservation_charge)
+                .filter(|amount| *amount > 0.0),
+            payment_type: payment
+                .and_then(|p| p.config.as_ref())
+                .and_then(|c| c.payment_type.clone()),
+            policy_text: details
+                .cancellation
+                .as_ref()
+                .and_then(|c| c.display.as_ref())
+                .map(|d| d.policy.clone())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Reject the slot if any configured limit is exceeded
+    pub fn check(&self, limits: &PolicyLimits) -> Result<(), PolicyViolation> {
+        if let (Some(fee), Some(max)) = (self.cancellation_fee, limits.max_cancellation_fee) {
+            if fee > max {
+                return Err(PolicyViolation(format!(
+                    "cancellation fee ${:.2}/person exceeds --max-cancellation-fee ${:.2}",
+                    fee, max
+                )));
+            }
+        }
+        if let (Some(deposit), Some(max)) = (self.deposit, limits.max_deposit) {
+            if deposit > max {
+                return Err(PolicyViolation(format!(
+                    "deposit ${:.2} exceeds --max-deposit ${:.2}",
+                    deposit, max
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Lines suitable for the booking log
+    pub fn describe(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+
+        match self.cancellation_fee {
+            Some(fee) => {
+                let cut_off = self
+                    .cancellation_cut_off
+                    .as_deref()
+                    .map(|c| format!(" (after {})", c))
+                    .unwrap_or_default();
+                lines.push(format!("Cancellation fee: ${:.2}/person{}", fee, cut_off));
+            }
+            None => lines.push("Cancellation fee: none".to_string()),
+        }
+        match self.deposit {
+            Some(deposit) => lines.push(format!(
+                "Deposit: ${:.2}{}",
+                deposit,
+                self.payment_type
+                    .as_deref()
+                    .map(|t| format!(" ({})", t))
+                    .unwrap_or_default()
+            )),
+            None => lines.push("Deposit: none".to_string()),
+        }
+        lines.extend(self.policy_text.iter().map(|text| format!("Policy: {}", text)));
+
+        lines
+    }
+}
+
+/// A slot whose cancellation fee or deposit is over the configured limits
+#[derive(Debug)]
+pub struct PolicyViolation(pub String);
+
+impl fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Slot rejected: {}", self.0)
+    }
+}
+
+impl std::error::Error for PolicyViolation {}
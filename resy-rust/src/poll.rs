@@ -0,0 +1,213 @@
+use anyhow::Result;
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::LoggerHandle;
+
+fn default_lead_secs() -> u64 {
+    30
+}
+
+fn default_sparse_ms() -> u64 {
+    2000
+}
+
+fn default_window_before_secs() -> u64 {
+    3
+}
+
+fn default_window_after_secs() -> u64 {
+    30
+}
+
+fn default_backoff_max_ms() -> u64 {
+    5000
+}
+
+/// How often to poll relative to the predicted release: sparsely before it,
+/// every --poll-interval-ms in a window around it, and backing off after it.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, clap::Args)]
+pub struct PollSchedule {
+    /// Start polling this many seconds before a known release (--release-at or a scheduled job)
+    #[arg(long = "poll-lead-secs", value_name = "SECS", default_value_t = default_lead_secs())]
+    #[serde(default = "default_lead_secs")]
+    pub lead_secs: u64,
+
+    /// Poll interval in milliseconds before the release window opens
+    #[arg(long = "poll-sparse-ms", value_name = "MS", default_value_t = default_sparse_ms())]
+    #[serde(default = "default_sparse_ms")]
+    pub sparse_ms: u64,
+
+    /// The tight release window opens this many seconds before the predicted release
+    #[arg(long = "poll-window-before-secs", value_name = "SECS", default_value_t = default_window_before_secs())]
+    #[serde(default = "default_window_before_secs")]
+    pub window_before_secs: u64,
+
+    /// The release window closes this many seconds after the predicted release
+    #[arg(long = "poll-window-after-secs", value_name = "SECS", default_value_t = default_window_after_secs())]
+    #[serde(default = "default_window_after_secs")]
+    pub window_after_secs: u64,
+
+    /// After the window, double the poll interval up to this many milliseconds
+    #[arg(long = "poll-backoff-max-ms", value_name = "MS", default_value_t = default_backoff_max_ms())]
+    #[serde(default = "default_backoff_max_ms")]
+    pub backoff_max_ms: u64,
+}
+
+impl Default for PollSchedule {
+    fn default() -> Self {
+        Self {
+            lead_secs: default_lead_secs(),
+            sparse_ms: default_sparse_ms(),
+            window_before_secs: default_window_before_secs(),
+            window_after_secs: default_window_after_secs(),
+            backoff_max_ms: default_backoff_max_ms(),
+        }
+    }
+}
+
+/// Where polling is relative to the release window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PollPhase {
+    Sparse,
+    Window,
+    Backoff,
+}
+
+impl PollPhase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PollPhase::Sparse => "before the release window",
+            PollPhase::Window => "in the release window",
+            PollPhase::Backoff => "after the release window",
+        }
+    }
+}
+
+impl PollSchedule {
+    pub fn lead(&self) -> Duration {
+        Duration::from_secs(self.lead_secs)
+    }
+
+    fn window_start(&self, release: DateTime<Utc>) -> DateTime<Utc> {
+        release - chrono::Duration::seconds(self.window_before_secs as i64)
+    }
+
+    pub fn phase(&self, now: DateTime<Utc>, release: DateTime<Utc>) -> PollPhase {
+        if now < self.window_start(release) {
+            PollPhase::Sparse
+        } else if now <= release + chrono::Duration::seconds(self.window_after_secs as i64) {
+            PollPhase::Window
+        } else {
+            PollPhase::Backoff
+        }
+    }
+
+    /// Delay before the next poll. `interval` is the in-window interval and
+    /// `backoff_polls` counts polls since the window closed. A sparse delay never
+    /// runs past the start of the window.
+    pub fn next_delay(&self, now: DateTime<Utc>, release: DateTime<Utc>, interval: Duration, backoff_polls: u32) -> Duration {
+        match self.phase(now, release) {
+            PollPhase::Sparse => {
+                let until_window = (self.window_start(release) - now).to_std().unwrap_or_default();
+                Duration::from_millis(self.sparse_ms).max(interval).min(until_window)
+            }
+            PollPhase::Window => interval,
+            PollPhase::Backoff => interval
+                .saturating_mul(2u32.saturating_pow(backoff_polls.min(16)))
+                .min(Duration::from_millis(self.backoff_max_ms).max(interval)),
+        }
+    }
+
+    /// One line for the startup banner
+    pub fn describe(&self, interval: Duration) -> String {
+        format!(
+            "every {}ms until {}s before release, {}ms until {}s after, then backing off to {}ms",
+            self.sparse_ms,
+            self.window_before_secs,
+            interval.as_millis(),
+            self.window_after_secs,
+            self.backoff_max_ms.max(interval.as_millis() as u64)
+        )
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.sparse_ms == 0 || self.backoff_max_ms == 0 {
+            anyhow::bail!("Poll intervals must be at least 1ms");
+        }
+        Ok(())
+    }
+}
+
+/// Sleep until polling should start for a release at `release`
+pub async fn wait_for_release(release: DateTime<Utc>, schedule: &PollSchedule, logger: &LoggerHandle) {
+    let start_at = release - chrono::Duration::from_std(schedule.lead()).unwrap_or_default();
+    if let Ok(wait) = (start_at - Utc::now()).to_std() {
+        logger.log(&format!("⏰ Waiting {:.1}s to start polling {}s before the release at {}",
+            wait.as_secs_f64(), schedule.lead_secs, release.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S")));
+        tokio::time::sleep(wait).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INTERVAL: Duration = Duration::from_millis(100);
+
+    fn release() -> DateTime<Utc> {
+        "2026-01-01T09:00:00Z".parse().unwrap()
+    }
+
+    fn at(offset_ms: i64) -> DateTime<Utc> {
+        release() + chrono::Duration::milliseconds(offset_ms)
+    }
+
+    #[test]
+    fn phase_boundaries() {
+        let schedule = PollSchedule::default();
+        assert_eq!(schedule.phase(at(-3001), release()), PollPhase::Sparse);
+        assert_eq!(schedule.phase(at(-3000), release()), PollPhase::Window);
+        assert_eq!(schedule.phase(at(30_000), release()), PollPhase::Window);
+        assert_eq!(schedule.phase(at(30_001), release()), PollPhase::Backoff);
+    }
+
+    #[test]
+    fn sparse_delay_is_clamped_to_window_start() {
+        let schedule = PollSchedule::default();
+        assert_eq!(schedule.next_delay(at(-20_000), release(), INTERVAL, 0), Duration::from_millis(2000));
+        assert_eq!(schedule.next_delay(at(-3500), release(), INTERVAL, 0), Duration::from_millis(500));
+        // A slower in-window interval still wins over the sparse one
+        assert_eq!(
+            schedule.next_delay(at(-20_000), release(), Duration::from_millis(5000), 0),
+            Duration::from_millis(5000)
+        );
+    }
+
+    #[test]
+    fn window_delay_is_the_interval() {
+        let schedule = PollSchedule::default();
+        assert_eq!(schedule.next_delay(at(-3000), release(), INTERVAL, 0), INTERVAL);
+        assert_eq!(schedule.next_delay(at(10_000), release(), INTERVAL, 7), INTERVAL);
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let schedule = PollSchedule::default();
+        let delays: Vec<_> = (0..8)
+            .map(|polls| schedule.next_delay(at(60_000), release(), INTERVAL, polls).as_millis())
+            .collect();
+        assert_eq!(delays, [100, 200, 400, 800, 1600, 3200, 5000, 5000]);
+        assert_eq!(
+            schedule.next_delay(at(60_000), release(), INTERVAL, u32::MAX),
+            Duration::from_millis(5000)
+        );
+    }
+
+    #[test]
+    fn backoff_never_drops_below_the_interval() {
+        let schedule = PollSchedule { backoff_max_ms: 50, ..Default::default() };
+        assert_eq!(schedule.next_delay(at(60_000), release(), INTERVAL, 3), INTERVAL);
+    }
+}
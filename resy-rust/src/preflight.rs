@@ -0,0 +1,290 @@
+use anyhow::Result;
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use std::fs;
+use std::time::Duration;
+
+use crate::api::ResyClient;
+use crate::config::{Config, NetworkConfig};
+use crate::credentials::{CredentialCache, Credentials};
+use crate::latency;
+use crate::schedule::Job;
+use crate::token::{self, TokenStatus};
+use crate::types::{User, UserResponse};
+
+/// How a single check came out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Verdict {
+    Pass,
+    Warn,
+    Fail,
+}
+
+impl Verdict {
+    fn icon(&self) -> &'static str {
+        match self {
+            Verdict::Pass => "✅",
+            Verdict::Warn => "⚠️ ",
+            Verdict::Fail => "❌",
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Finding {
+    verdict: Verdict,
+    check: &'static str,
+    message: String,
+}
+
+/// Results of every check, printed as they come in
+#[derive(Debug, Default)]
+pub struct Report {
+    findings: Vec<Finding>,
+}
+
+impl Report {
+    fn record(&mut self, verdict: Verdict, check: &'static str, message: impl Into<String>) {
+        let finding = Finding { verdict, check, message: message.into() };
+        println!("   {} {:<12} {}", finding.verdict.icon(), finding.check, finding.message);
+        self.findings.push(finding);
+    }
+
+    fn pass(&mut self, check: &'static str, message: impl Into<String>) {
+        self.record(Verdict::Pass, check, message);
+    }
+
+    fn warn(&mut self, check: &'static str, message: impl Into<String>) {
+        self.record(Verdict::Warn, check, message);
+    }
+
+    fn fail(&mut self, check: &'static str, message: impl Into<String>) {
+        self.record(Verdict::Fail, check, message);
+    }
+
+    fn count(&self, verdict: Verdict) -> usize {
+        self.findings.iter().filter(|finding| finding.verdict == verdict).count()
+    }
+
+    /// Print the totals and fail if any check failed
+    pub fn finish(&self) -> Result<()> {
+        let failed = self.count(Verdict::Fail);
+        println!();
+        println!(
+            "Pre-flight: {} passed, {} warning(s), {} failed",
+            self.count(Verdict::Pass),
+            self.count(Verdict::Warn),
+            failed
+        );
+        if failed > 0 {
+            anyhow::bail!("{} pre-flight check(s) failed", failed);
+        }
+        Ok(())
+    }
+}
+
+/// Limits above which a check warns
+#[derive(Debug, Clone, Copy)]
+pub struct Thresholds {
+    /// Token must stay valid at least this long after the release
+    pub token_margin: chrono::Duration,
+    pub max_clock_offset: Duration,
+    /// For DNS resolution and connection setup each
+    pub max_latency: Duration,
+}
+
+/// Checks that don't depend on a job or an account: clock, network and log directory
+pub async fn check_environment(network: &NetworkConfig, log_dir: &std::path::Path, thresholds: &Thresholds, report: &mut Report) {
+    println!("🛫 Environment");
+
+    // None of these requests are authenticated
+    let client = match ResyClient::new(Credentials {
+        profile: "preflight".to_string(),
+        api_key: String::new(),
+        auth_token: String::new(),
+        login: None,
+    })
+    .and_then(|client| client.with_network(network))
+    {
+        Ok(client) => client,
+        Err(e) => {
+            report.fail("network", format!("{:#}", e));
+            return;
+        }
+    };
+
+    match client.clock_offset().await {
+        Ok(offset) => {
+            let message = format!("Resy clock is {:+.3}s from ours", offset.num_milliseconds() as f64 / 1000.0);
+            if offset.abs().to_std().unwrap_or_default() > thresholds.max_clock_offset {
+                report.warn("clock", format!("{} (scheduled jobs correct for this; book doesn't)", message));
+            } else {
+                report.pass("clock", message);
+            }
+        }
+        Err(e) => report.fail("clock", format!("{:#}", e)),
+    }
+
+    match latency::measure(client.network()).await {
+        Ok(phases) => {
+            let host = client.network().host_port().map(|(host, _)| host).unwrap_or_default();
+            let message = format!("resolved {} in {}ms", host, phases.dns.as_millis());
+            latency_verdict(report, "dns", phases.dns, thresholds, message);
+            let message = match phases.tls {
+                Some(tls) => format!("TCP connect {}ms, TLS handshake {}ms", phases.connect.as_millis(), tls.as_millis()),
+                None => format!("TCP connect {}ms (no TLS)", phases.connect.as_millis()),
+            };
+            latency_verdict(report, "tls", phases.setup(), thresholds, message);
+        }
+        Err(e) => report.fail("network", format!("{:#}", e)),
+    }
+
+    let probe = log_dir.join(format!(".preflight-{}", std::process::id()));
+    let writable = fs::create_dir_all(log_dir).and_then(|_| fs::write(&probe, b"")).and_then(|_| fs::remove_file(&probe));
+    match writable {
+        Ok(()) => report.pass("log dir", format!("{} is writable", log_dir.display())),
+        Err(e) => report.fail("log dir", format!("Cannot write to {}: {}", log_dir.display(), e)),
+    }
+}
+
+fn latency_verdict(report: &mut Report, check: &'static str, elapsed: Duration, thresholds: &Thresholds, message: String) {
+    if elapsed > thresholds.max_latency {
+        report.warn(check, format!("{} (over {}ms)", message, thresholds.max_latency.as_millis()));
+    } else {
+        report.pass(check, message);
+    }
+}
+
+/// Credentials and token expiry for `profiles`, which must stay valid until
+/// `needed_at`. Returns a client for the first account, with the user of the
+/// first account that authenticated.
+pub async fn check_accounts(
+    credentials: &CredentialCache,
+    config: &Config,
+    profiles: &[String],
+    needed_at: DateTime<Utc>,
+    thresholds: &Thresholds,
+    report: &mut Report,
+) -> Option<(ResyClient, Option<UserResponse>)> {
+    let accounts = match credentials.resolve(config, profiles) {
+        Ok(accounts) => accounts,
+        Err(e) => {
+            report.fail("credentials", format!("{:#}", e));
+            return None;
+        }
+    };
+
+    let mut first: Option<(ResyClient, Option<UserResponse>)> = None;
+    for account in accounts {
+        let profile = account.profile.clone();
+        let client = match ResyClient::new(account).and_then(|client| client.with_network(&config.network)) {
+            Ok(client) => client,
+            Err(e) => {
+                report.fail("credentials", format!("{}: {:#}", profile, e));
+                continue;
+            }
+        };
+
+        let user = match client.fetch_user().await {
+            Ok(user) => {
+                report.pass("credentials", format!("{}: authenticated", profile));
+                Some(user)
+            }
+            Err(e) => {
+                report.fail("credentials", format!("{}: {}", profile, e));
+                None
+            }
+        };
+
+        let needed_at_local = needed_at.with_timezone(&Local).format("%Y-%m-%d %H:%M");
+        match TokenStatus::at(&client.auth_token(), needed_at) {
+            TokenStatus::Unknown => report.warn("token", format!("{}: expiry unknown (token is not a JWT)", profile)),
+            TokenStatus::Expired { expires_at } => report.fail(
+                "token",
+                format!("{}: expires {}, before {}", profile, expires_at.with_timezone(&Local).format("%Y-%m-%d %H:%M"), needed_at_local),
+            ),
+            TokenStatus::Valid { expires_at } | TokenStatus::ExpiringSoon { expires_at } => {
+                let margin = expires_at - needed_at;
+                let message = format!("{}: still valid for {} at {}", profile, token::format_remaining(margin), needed_at_local);
+                if margin < thresholds.token_margin {
+                    report.warn("token", message);
+                } else {
+                    report.pass("token", message);
+                }
+            }
+        }
+
+        match &mut first {
+            None => first = Some((client, user)),
+            Some((_, first_user @ None)) => *first_user = user,
+            Some(_) => {}
+        }
+    }
+    first
+}
+
+/// Everything a scheduled job needs when its release comes around
+pub async fn check_job(job: &Job, credentials: &CredentialCache, config: &Config, thresholds: &Thresholds, report: &mut Report) {
+    println!();
+    println!("🛫 {} ({}), release {}", job.venue_label(), job.id, job.release_local());
+
+    if job.release_at <= Utc::now() {
+        report.warn("release", "the release time has already passed");
+    }
+
+    let Some((client, user)) = check_accounts(credentials, config, &job.profiles, job.release_at, thresholds, report).await else {
+        return;
+    };
+
+    match client.fetch_venue_details(&job.venue_id).await {
+        Ok(venue) => {
+            report.pass("venue", format!("{} exists", venue.venue.name));
+            match venue.lead_time_in_days.or(job.lead_days) {
+                Some(lead_days) => check_lead_window(job, lead_days, venue.lead_time_in_days, report),
+                None => report.warn("lead window", "the venue doesn't report a lead time"),
+            }
+        }
+        Err(e) => {
+            report.fail("venue", format!("venue {}: {}", job.venue_id, e));
+            if let Some(lead_days) = job.lead_days {
+                check_lead_window(job, lead_days, None, report);
+            }
+        }
+    }
+
+    let Some(user) = user else {
+        report.warn("payment", "not checked: no account authenticated");
+        return;
+    };
+    let user = User { payment_methods: user.payment_methods };
+    match user.select_payment_method(job.payment_method_id) {
+        Ok(Some(method)) => report.pass("payment", format!("{} ({})", method.id, method.describe())),
+        Ok(None) => report.warn("payment", "no card on file; venues that take a deposit can't be booked"),
+        Err(e) => report.fail("payment", format!("{:#}", e)),
+    }
+}
+
+/// Whether every date will be bookable at the job's release, given the venue's lead time
+fn check_lead_window(job: &Job, lead_days: u32, venue_lead_days: Option<u32>, report: &mut Report) {
+    if let (Some(venue), Some(job_lead)) = (venue_lead_days, job.lead_days) {
+        if venue != job_lead && !job.lead_days_from_venue {
+            report.warn("lead window", format!("the venue now opens {} days ahead; the job assumes {}", venue, job_lead));
+        }
+    }
+
+    let today = Local::now().date_naive();
+    let release_day = job.release_at.with_timezone(&Local).date_naive();
+    for date in &job.dates {
+        let Ok(day) = NaiveDate::parse_from_str(date, "%Y-%m-%d") else {
+            report.fail("lead window", format!("{}: not a YYYY-MM-DD date", date));
+            continue;
+        };
+        let ahead = (day - release_day).num_days();
+        if day < today {
+            report.fail("lead window", format!("{} is in the past", date));
+        } else if ahead > lead_days as i64 {
+            report.fail("lead window", format!("{} is {} days after the release, beyond the {}-day window", date, ahead, lead_days));
+        } else {
+            report.pass("lead window", format!("{} opens within the {}-day window", date, lead_days));
+        }
+    }
+}
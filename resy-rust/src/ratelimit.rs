@@ -0,0 +1,228 @@
+use anyhow::Result;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::config::RateLimitConfig;
+
+/// Never drop below this rate, however many 429s come back
+const MIN_RATE: f64 = 0.5;
+
+/// Pause after a 429 without a usable Retry-After header
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(1);
+
+/// Longest Retry-After honored, so a bogus header can't stall a run for hours
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// Each successful response recovers this fraction of the configured rate
+const RECOVERY_STEP: f64 = 0.05;
+
+/// Token bucket shared by every client and task in a run. Each request takes a
+/// token; a 429 halves the rate and pauses everyone until its Retry-After.
+#[derive(Debug)]
+pub struct RateLimiter {
+    limits: RateLimitConfig,
+    bucket: Mutex<Bucket>,
+    requests: AtomicUsize,
+    throttled: AtomicUsize,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    /// Current refill rate; below the configured rate after 429s
+    rate: f64,
+    refilled: Instant,
+    paused_until: Option<Instant>,
+}
+
+impl Bucket {
+    fn refill(&mut self, now: Instant, burst: f64) {
+        let elapsed = now.duration_since(self.refilled).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(burst);
+        self.refilled = now;
+    }
+}
+
+/// The run used up its request budget
+#[derive(Debug)]
+pub struct BudgetExhausted(pub usize);
+
+impl fmt::Display for BudgetExhausted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Request budget of {} exhausted", self.0)
+    }
+}
+
+impl std::error::Error for BudgetExhausted {}
+
+impl RateLimiter {
+    pub fn new(limits: RateLimitConfig) -> Self {
+        let bucket = Bucket {
+            tokens: limits.burst.max(1) as f64,
+            rate: limits.requests_per_sec,
+            refilled: Instant::now(),
+            paused_until: None,
+        };
+        Self {
+            limits,
+            bucket: Mutex::new(bucket),
+            requests: AtomicUsize::new(0),
+            throttled: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn limits(&self) -> &RateLimitConfig {
+        &self.limits
+    }
+
+    /// Requests sent so far
+    pub fn requests(&self) -> usize {
+        self.requests.load(Ordering::Relaxed)
+    }
+
+    /// 429 responses received so far
+    pub fn throttled(&self) -> usize {
+        self.throttled.load(Ordering::Relaxed)
+    }
+
+    /// Wait for a token. Fails with [`BudgetExhausted`] once the run's budget is spent.
+    pub async fn acquire(&self) -> Result<()> {
+        loop {
+            let wait = {
+                let mut bucket = self.bucket.lock().unwrap();
+                if let Some(budget) = self.limits.request_budget {
+                    if self.requests() >= budget {
+                        return Err(BudgetExhausted(budget).into());
+                    }
+                }
+
+                let now = Instant::now();
+                bucket.refill(now, self.limits.burst.max(1) as f64);
+                match bucket.paused_until.filter(|until| *until > now) {
+                    Some(until) => until - now,
+                    None if bucket.tokens >= 1.0 => {
+                        bucket.tokens -= 1.0;
+                        self.requests.fetch_add(1, Ordering::Relaxed);
+                        return Ok(());
+                    }
+                    None => Duration::from_secs_f64((1.0 - bucket.tokens) / bucket.rate),
+                }
+            };
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Back off after a 429: halve the rate and pause until `retry_after`
+    pub fn on_rate_limited(&self, retry_after: Option<Duration>) {
+        self.throttled.fetch_add(1, Ordering::Relaxed);
+        let pause = retry_after.unwrap_or(DEFAULT_RETRY_AFTER).min(MAX_RETRY_AFTER);
+
+        let mut bucket = self.bucket.lock().unwrap();
+        let now = Instant::now();
+        bucket.refill(now, self.limits.burst.max(1) as f64);
+        bucket.rate = (bucket.rate / 2.0).max(MIN_RATE.min(self.limits.requests_per_sec));
+        bucket.tokens = 0.0;
+        let until = now + pause;
+        if bucket.paused_until.is_none_or(|current| current < until) {
+            bucket.paused_until = Some(until);
+        }
+    }
+
+    /// Creep back towards the configured rate after a response that wasn't a 429
+    pub fn on_success(&self) {
+        let mut bucket = self.bucket.lock().unwrap();
+        if bucket.rate < self.limits.requests_per_sec {
+            let now = Instant::now();
+            bucket.refill(now, self.limits.burst.max(1) as f64);
+            bucket.rate = (bucket.rate + self.limits.requests_per_sec * RECOVERY_STEP).min(self.limits.requests_per_sec);
+        }
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new(RateLimitConfig::default())
+    }
+}
+
+/// Parse a Retry-After header, given either in seconds or as an HTTP date
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<f64>() {
+        return (secs.is_finite() && secs >= 0.0).then(|| Duration::from_secs_f64(secs));
+    }
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    Some((at.with_timezone(&chrono::Utc) - chrono::Utc::now()).to_std().unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn headers(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    fn limits(requests_per_sec: f64, burst: u32, request_budget: Option<usize>) -> RateLimitConfig {
+        RateLimitConfig { requests_per_sec, burst, request_budget }
+    }
+
+    #[test]
+    fn retry_after_reads_seconds() {
+        assert_eq!(retry_after(&headers("3")), Some(Duration::from_secs(3)));
+        assert_eq!(retry_after(&headers(" 1.5 ")), Some(Duration::from_millis(1500)));
+        assert_eq!(retry_after(&headers("-1")), None);
+        assert_eq!(retry_after(&headers("soon")), None);
+        assert_eq!(retry_after(&HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn retry_after_reads_http_dates() {
+        let at = chrono::Utc::now() + chrono::Duration::seconds(30);
+        let wait = retry_after(&headers(&at.format("%a, %d %b %Y %H:%M:%S GMT").to_string())).unwrap();
+        assert!(wait > Duration::from_secs(25) && wait <= Duration::from_secs(30), "{:?}", wait);
+
+        assert_eq!(retry_after(&headers("Wed, 21 Oct 2015 07:28:00 GMT")), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn bucket_refills_at_the_rate_up_to_the_burst() {
+        let start = Instant::now();
+        let mut bucket = Bucket { tokens: 0.0, rate: 4.0, refilled: start, paused_until: None };
+
+        bucket.refill(start + Duration::from_millis(500), 5.0);
+        assert!((bucket.tokens - 2.0).abs() < 1e-9, "{}", bucket.tokens);
+
+        bucket.refill(start + Duration::from_secs(10), 5.0);
+        assert_eq!(bucket.tokens, 5.0);
+    }
+
+    #[tokio::test]
+    async fn acquire_stops_at_the_request_budget() {
+        let limiter = RateLimiter::new(limits(1000.0, 10, Some(3)));
+        for _ in 0..3 {
+            limiter.acquire().await.unwrap();
+        }
+        let error = limiter.acquire().await.unwrap_err();
+        assert_eq!(error.downcast_ref::<BudgetExhausted>().map(|e| e.0), Some(3));
+        assert_eq!(limiter.requests(), 3);
+    }
+
+    #[test]
+    fn rate_limited_halves_the_rate_and_pauses() {
+        let limiter = RateLimiter::new(limits(8.0, 4, None));
+        limiter.on_rate_limited(Some(Duration::from_secs(2)));
+
+        let bucket = limiter.bucket.lock().unwrap();
+        assert_eq!(bucket.rate, 4.0);
+        assert_eq!(bucket.tokens, 0.0);
+        assert!(bucket.paused_until.unwrap() > Instant::now() + Duration::from_secs(1));
+        assert_eq!(limiter.throttled(), 1);
+    }
+}
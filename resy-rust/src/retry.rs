@@ -0,0 +1,304 @@
+use anyhow::Result;
+use clap::ValueEnum;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+use crate::api::{HttpError, SlotTaken};
+use crate::breaker::FatalError;
+
+/// Kinds of failure a retry policy can choose to retry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum ErrorClass {
+    /// Connection failures and dropped requests
+    Network,
+    /// Requests that took longer than the client timeout
+    Timeout,
+    /// HTTP 429
+    RateLimited,
+    /// HTTP 5xx
+    Server,
+    /// HTTP 401/403
+    Auth,
+    /// Any other HTTP 4xx, e.g. a slot that is no longer available
+    Client,
+    /// Anything else, e.g. an unparseable response
+    Other,
+}
+
+impl ErrorClass {
+    pub fn of(error: &anyhow::Error) -> Self {
+        for cause in error.chain() {
+            if cause.downcast_ref::<FatalError>().is_some() {
+                return ErrorClass::Auth;
+            }
+            if let Some(taken) = cause.downcast_ref::<SlotTaken>() {
+                return Self::of_status(taken.status);
+            }
+            if let Some(http) = cause.downcast_ref::<HttpError>() {
+                return Self::of_status(http.status);
+            }
+            if let Some(e) = cause.downcast_ref::<reqwest::Error>() {
+                if e.is_timeout() {
+                    return ErrorClass::Timeout;
+                }
+                if let Some(status) = e.status() {
+                    return Self::of_status(status);
+                }
+                if e.is_connect() || e.is_request() || e.is_body() {
+                    return ErrorClass::Network;
+                }
+            }
+        }
+        ErrorClass::Other
+    }
+
+    fn of_status(status: StatusCode) -> Self {
+        match status {
+            StatusCode::TOO_MANY_REQUESTS => ErrorClass::RateLimited,
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => ErrorClass::Auth,
+            status if status.is_server_error() => ErrorClass::Server,
+            status if status.is_client_error() => ErrorClass::Client,
+            _ => ErrorClass::Other,
+        }
+    }
+}
+
+fn default_base_delay_ms() -> u64 {
+    50
+}
+
+fn default_multiplier() -> f64 {
+    2.0
+}
+
+fn default_max_delay_ms() -> u64 {
+    1000
+}
+
+fn default_jitter() -> f64 {
+    0.2
+}
+
+fn default_retry_on() -> Vec<ErrorClass> {
+    vec![
+        ErrorClass::Network,
+        ErrorClass::Timeout,
+        ErrorClass::RateLimited,
+        ErrorClass::Server,
+        // /4/find can return a 4xx before a venue releases its slots
+        ErrorClass::Client,
+        ErrorClass::Other,
+    ]
+}
+
+/// How failed polls and booking attempts are retried: exponential backoff
+/// with jitter, capped per delay and in total, for selected error classes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    /// Delay before the first retry
+    #[serde(default = "default_base_delay_ms")]
+    pub base_delay_ms: u64,
+    /// Each retry waits this many times longer than the last
+    #[serde(default = "default_multiplier")]
+    pub multiplier: f64,
+    /// Longest delay between two attempts
+    #[serde(default = "default_max_delay_ms")]
+    pub max_delay_ms: u64,
+    /// Randomize each delay by up to this fraction either way
+    #[serde(default = "default_jitter")]
+    pub jitter: f64,
+    /// Stop retrying this long after the first attempt (default: no limit)
+    #[serde(default)]
+    pub max_elapsed_ms: Option<u64>,
+    /// Error classes worth retrying; others move on to the next slot or stop polling
+    #[serde(default = "default_retry_on")]
+    pub retry_on: Vec<ErrorClass>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            base_delay_ms: default_base_delay_ms(),
+            multiplier: default_multiplier(),
+            max_delay_ms: default_max_delay_ms(),
+            jitter: default_jitter(),
+            max_elapsed_ms: None,
+            retry_on: default_retry_on(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before retry number `retry` (0 for the first retry)
+    pub fn delay(&self, retry: u32) -> Duration {
+        let exponential = self.base_delay_ms as f64 * self.multiplier.powi(retry.min(64) as i32);
+        let capped = exponential.min(self.max_delay_ms as f64);
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        let factor = 1.0 + jitter * (fastrand::f64() * 2.0 - 1.0);
+        Duration::from_secs_f64((capped * factor).max(0.0) / 1000.0)
+    }
+
+    pub fn is_retryable(&self, error: &anyhow::Error) -> bool {
+        self.retry_on.contains(&ErrorClass::of(error))
+    }
+
+    /// Whether retrying has gone on longer than max_elapsed_ms
+    pub fn out_of_time(&self, started: Instant) -> bool {
+        self.max_elapsed_ms
+            .is_some_and(|max| started.elapsed() >= Duration::from_millis(max))
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if !(self.multiplier.is_finite() && self.multiplier >= 1.0) {
+            anyhow::bail!("retry.multiplier must be at least 1");
+        }
+        if !(0.0..=1.0).contains(&self.jitter) {
+            anyhow::bail!("retry.jitter must be between 0 and 1");
+        }
+        if self.max_delay_ms < self.base_delay_ms {
+            anyhow::bail!("retry.max_delay_ms must be at least retry.base_delay_ms");
+        }
+        Ok(())
+    }
+
+    /// One line for the startup banner
+    pub fn describe(&self) -> String {
+        let classes: Vec<_> = self
+            .retry_on
+            .iter()
+            .filter_map(|class| class.to_possible_value().map(|v| v.get_name().to_string()))
+            .collect();
+        let mut line = format!(
+            "{}ms × {} up to {}ms (±{:.0}%), on {}",
+            self.base_delay_ms,
+            self.multiplier,
+            self.max_delay_ms,
+            self.jitter * 100.0,
+            if classes.is_empty() { "nothing".to_string() } else { classes.join(", ") }
+        );
+        if let Some(max) = self.max_elapsed_ms {
+            line.push_str(&format!(", for at most {}ms", max));
+        }
+        line
+    }
+}
+
+/// Command-line overrides for the config file's [retry] policy
+#[derive(Debug, Clone, clap::Args)]
+pub struct RetryArgs {
+    /// Delay before the first retry, in milliseconds (overrides retry.base_delay_ms)
+    #[arg(long, global = true, value_name = "MS")]
+    retry_base_ms: Option<u64>,
+
+    /// Multiply the delay by this after each retry (overrides retry.multiplier)
+    #[arg(long, global = true)]
+    retry_multiplier: Option<f64>,
+
+    /// Longest delay between retries, in milliseconds (overrides retry.max_delay_ms)
+    #[arg(long, global = true, value_name = "MS")]
+    retry_max_ms: Option<u64>,
+
+    /// Randomize delays by up to this fraction, 0-1 (overrides retry.jitter)
+    #[arg(long, global = true)]
+    retry_jitter: Option<f64>,
+
+    /// Stop retrying this many milliseconds after the first attempt (overrides retry.max_elapsed_ms)
+    #[arg(long, global = true, value_name = "MS")]
+    retry_max_elapsed_ms: Option<u64>,
+
+    /// Error classes to retry (overrides retry.retry_on)
+    #[arg(long, global = true, value_enum, value_delimiter = ',')]
+    retry_on: Option<Vec<ErrorClass>>,
+}
+
+impl RetryArgs {
+    pub fn apply(&self, policy: &mut RetryPolicy) {
+        if let Some(ms) = self.retry_base_ms {
+            policy.base_delay_ms = ms;
+        }
+        if let Some(multiplier) = self.retry_multiplier {
+            policy.multiplier = multiplier;
+        }
+        if let Some(ms) = self.retry_max_ms {
+            policy.max_delay_ms = ms;
+        }
+        if let Some(jitter) = self.retry_jitter {
+            policy.jitter = jitter;
+        }
+        if let Some(ms) = self.retry_max_elapsed_ms {
+            policy.max_elapsed_ms = Some(ms);
+        }
+        if let Some(classes) = &self.retry_on {
+            policy.retry_on = classes.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::breaker::FatalReason;
+
+    fn http_error(status: u16) -> anyhow::Error {
+        anyhow::Error::new(HttpError {
+            action: "Failed to fetch slots",
+            status: StatusCode::from_u16(status).unwrap(),
+        })
+    }
+
+    #[test]
+    fn delay_grows_and_is_capped_without_jitter() {
+        let policy = RetryPolicy { jitter: 0.0, ..Default::default() };
+        let delays: Vec<_> = (0..6).map(|retry| policy.delay(retry).as_millis()).collect();
+        assert_eq!(delays, [50, 100, 200, 400, 800, 1000]);
+        assert_eq!(policy.delay(u32::MAX), Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn delay_stays_within_jitter_bounds() {
+        let policy = RetryPolicy { jitter: 0.5, ..Default::default() };
+        for _ in 0..200 {
+            let delay = policy.delay(1);
+            assert!(delay >= Duration::from_millis(50) && delay <= Duration::from_millis(150), "{:?}", delay);
+            let capped = policy.delay(10);
+            assert!(capped >= Duration::from_millis(500) && capped <= Duration::from_millis(1500), "{:?}", capped);
+        }
+    }
+
+    #[test]
+    fn error_classes_follow_the_status() {
+        assert_eq!(ErrorClass::of(&http_error(429)), ErrorClass::RateLimited);
+        assert_eq!(ErrorClass::of(&http_error(503)), ErrorClass::Server);
+        assert_eq!(ErrorClass::of(&http_error(401)), ErrorClass::Auth);
+        assert_eq!(ErrorClass::of(&http_error(412)), ErrorClass::Client);
+        assert_eq!(ErrorClass::of(&http_error(500).context("Booking attempt failed")), ErrorClass::Server);
+        assert_eq!(ErrorClass::of(&anyhow::anyhow!("Failed to parse slots")), ErrorClass::Other);
+    }
+
+    #[test]
+    fn default_policy_retries_everything_but_auth() {
+        let policy = RetryPolicy::default();
+        assert!(policy.is_retryable(&http_error(500)));
+        assert!(policy.is_retryable(&http_error(429)));
+        assert!(policy.is_retryable(&http_error(404)));
+        assert!(!policy.is_retryable(&http_error(403)));
+
+        let fatal = anyhow::Error::new(FatalError {
+            reason: FatalReason::InvalidApiKey,
+            profile: "default".to_string(),
+            status: StatusCode::BAD_REQUEST,
+        });
+        assert_eq!(ErrorClass::of(&fatal), ErrorClass::Auth);
+        assert!(!policy.is_retryable(&fatal));
+    }
+
+    #[test]
+    fn retry_on_limits_the_classes() {
+        let policy = RetryPolicy { retry_on: vec![ErrorClass::Server], ..Default::default() };
+        assert!(policy.is_retryable(&http_error(502)));
+        assert!(!policy.is_retryable(&http_error(429)));
+        assert!(!policy.is_retryable(&http_error(400)));
+    }
+}
@@ -0,0 +1,504 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDate, NaiveTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+
+use crate::api::{self, check_payment_method_accounts, BookingParams, ResyClient};
+use crate::config::{self, Config};
+use crate::credentials::CredentialCache;
+use crate::poll::PollSchedule;
+use crate::policy::PolicyLimits;
+use crate::token::TokenStatus;
+
+/// How a job books once its release instant arrives
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Strategy {
+    /// Poll from the release instant and race every matching slot across threads
+    #[default]
+    Competitive,
+    /// Watch for cancellations from the release instant until booked
+    Watch,
+}
+
+impl Strategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Competitive => "competitive",
+            Self::Watch => "watch",
+        }
+    }
+}
+
+/// Where a job is in its lifecycle
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    #[default]
+    Pending,
+    Running,
+    Booked,
+    Failed,
+    /// Skipped by the daemon until resumed
+    Paused,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Running => "running",
+            Self::Booked => "booked",
+            Self::Failed => "failed",
+            Self::Paused => "paused",
+            Self::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// What happened when the daemon ran a job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobOutcome {
+    pub finished_at: DateTime<Utc>,
+    pub message: String,
+    #[serde(default)]
+    pub reservation_id: Option<u64>,
+    #[serde(default)]
+    pub log_file: Option<PathBuf>,
+}
+
+/// A booking to run at a future release instant, stored in ~/.resy-rust/jobs/<id>.toml
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job {
+    pub id: String,
+    pub created_at: DateTime<Utc>,
+    pub venue_id: String,
+    #[serde(default)]
+    pub venue_name: Option<String>,
+    pub party_size: u32,
+    /// Reservation dates (YYYY-MM-DD)
+    pub dates: Vec<String>,
+    pub times: Vec<String>,
+    #[serde(default)]
+    pub types: Vec<String>,
+    #[serde(default)]
+    pub strategy: Strategy,
+    /// When reservations for the earliest date open
+    pub release_at: DateTime<Utc>,
+    /// Days of lead time release_at was computed from (unset for an explicit --release-at)
+    #[serde(default)]
+    pub lead_days: Option<u32>,
+    /// Whether lead_days came from the venue, so the daemon keeps it in sync
+    #[serde(default)]
+    pub lead_days_from_venue: bool,
+    /// Local time of day reservations are released (HH:MM:SS)
+    #[serde(default)]
+    pub release_time: Option<String>,
+    /// Profiles to book with (default: the config's default profile)
+    #[serde(default)]
+    pub profiles: Vec<String>,
+    pub threads: usize,
+    pub retries: usize,
+    pub poll_interval_ms: u64,
+    pub poll_timeout_secs: u64,
+    /// Seconds between checks for the watch strategy
+    #[serde(default = "default_watch_interval_secs")]
+    pub watch_interval_secs: u64,
+    #[serde(default)]
+    pub payment_method_id: Option<u64>,
+    #[serde(default)]
+    pub max_cancellation_fee: Option<f64>,
+    #[serde(default)]
+    pub max_deposit: Option<f64>,
+    /// Poll pacing around the release
+    #[serde(default)]
+    pub poll_schedule: PollSchedule,
+    #[serde(default)]
+    pub status: JobStatus,
+    #[serde(default)]
+    pub outcome: Option<JobOutcome>,
+}
+
+fn default_watch_interval_secs() -> u64 {
+    60
+}
+
+fn default_threads() -> usize {
+    api::DEFAULT_THREADS
+}
+
+fn default_retries() -> usize {
+    api::DEFAULT_RETRIES
+}
+
+fn default_poll_interval_ms() -> u64 {
+    api::DEFAULT_POLL_INTERVAL_MS
+}
+
+fn default_poll_timeout_secs() -> u64 {
+    api::DEFAULT_POLL_TIMEOUT_SECS
+}
+
+/// What to schedule, from `schedule add` or a JSON request to the daemon
+#[derive(Debug, Clone, Deserialize)]
+pub struct JobSpec {
+    pub venue_id: String,
+    pub party_size: u32,
+    pub dates: Vec<String>,
+    pub times: Vec<String>,
+    #[serde(default)]
+    pub types: Vec<String>,
+    #[serde(default)]
+    pub strategy: Strategy,
+    /// Override the venue's lead time
+    #[serde(default)]
+    pub lead_days: Option<u32>,
+    /// Local HH:MM:SS (default: the earliest of `times`)
+    #[serde(default)]
+    pub release_time: Option<String>,
+    /// Exact local release time, YYYY-MM-DD HH:MM:SS
+    #[serde(default)]
+    pub release_at: Option<String>,
+    #[serde(default)]
+    pub profiles: Vec<String>,
+    #[serde(default = "default_threads")]
+    pub threads: usize,
+    #[serde(default = "default_retries")]
+    pub retries: usize,
+    #[serde(default = "default_poll_interval_ms")]
+    pub poll_interval_ms: u64,
+    #[serde(default = "default_poll_timeout_secs")]
+    pub poll_timeout_secs: u64,
+    #[serde(default = "default_watch_interval_secs")]
+    pub watch_interval_secs: u64,
+    #[serde(default)]
+    pub payment_method_id: Option<u64>,
+    #[serde(default)]
+    pub max_cancellation_fee: Option<f64>,
+    #[serde(default)]
+    pub max_deposit: Option<f64>,
+    #[serde(default)]
+    pub poll_schedule: PollSchedule,
+}
+
+/// Validate a spec and work out its release instant from the venue's lead time.
+/// Refuses releases already past (for the competitive strategy) and releases
+/// after an account's auth token expires. Non-fatal problems go to `warn`.
+pub async fn create_job(
+    spec: JobSpec,
+    config: &Config,
+    credentials: &CredentialCache,
+    warn: &(dyn Fn(&str) + Sync),
+) -> Result<Job> {
+    for date in &spec.dates {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .context(format!("Invalid date '{}': expected YYYY-MM-DD", date))?;
+    }
+    if spec.dates.is_empty() || spec.times.is_empty() {
+        anyhow::bail!("A job needs at least one date and one time");
+    }
+    // Dates tried one after another would each start after the previous one's
+    // poll timeout, long after their release
+    if spec.strategy == Strategy::Competitive && spec.dates.len() > 1 {
+        anyhow::bail!("A competitive job books a single date; schedule one job per date, or use --strategy watch");
+    }
+    spec.poll_schedule.validate()?;
+    if spec.watch_interval_secs == 0 {
+        anyhow::bail!("The watch interval must be at least 1 second");
+    }
+    // The venue id becomes part of the job id
+    validate_id(&spec.venue_id)?;
+
+    let accounts = credentials.resolve(config, &spec.profiles)?;
+    check_payment_method_accounts(spec.payment_method_id, accounts.len())?;
+    let client = ResyClient::new(accounts[0].clone())?.with_network(&config.network)?;
+
+    // The venue's lead time is only needed when no explicit release is given
+    let venue = client.fetch_venue_details(&spec.venue_id).await;
+    let venue_name = match &venue {
+        Ok(venue) => Some(venue.venue.name.clone()),
+        Err(e) if spec.release_at.is_some() || spec.lead_days.is_some() => {
+            warn(&format!("Could not fetch venue {}: {}", spec.venue_id, e));
+            None
+        }
+        Err(e) => anyhow::bail!("Could not look up the venue's lead time: {}. Pass --lead-days or --release-at", e),
+    };
+
+    let lead_days_from_venue = spec.release_at.is_none() && spec.lead_days.is_none();
+    let lead_days = spec.lead_days.or(venue.as_ref().ok().and_then(|v| v.lead_time_in_days));
+    let release_time = spec.release_time.or(spec.times.iter().min().cloned());
+
+    let release_at = match &spec.release_at {
+        Some(release_at) => crate::parse_local_datetime(release_at)?,
+        None => earliest_release(
+            &spec.dates,
+            lead_days.context("The venue doesn't report a lead time. Pass --lead-days or --release-at")?,
+            release_time.as_deref().context("Pass --release-time or --times")?,
+        )?,
+    };
+
+    if release_at <= Utc::now() && spec.strategy == Strategy::Competitive {
+        anyhow::bail!(
+            "Reservations were released at {}; book now with `resy-rust book` or use --strategy watch",
+            release_at.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S")
+        );
+    }
+
+    // Refuse jobs that would run with an expired token
+    for account in &accounts {
+        if let TokenStatus::Expired { expires_at } = TokenStatus::at(&account.auth_token, release_at) {
+            anyhow::bail!(
+                "Auth token for profile '{}' expires at {}, before the release at {}. Refresh it first",
+                account.profile,
+                expires_at.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S"),
+                release_at.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S")
+            );
+        }
+    }
+
+    Ok(Job {
+        id: Job::new_id(&spec.venue_id, &spec.dates),
+        created_at: Utc::now(),
+        venue_id: spec.venue_id,
+        venue_name,
+        party_size: spec.party_size,
+        dates: spec.dates,
+        times: spec.times,
+        types: spec.types,
+        strategy: spec.strategy,
+        release_at,
+        lead_days,
+        lead_days_from_venue,
+        release_time,
+        profiles: spec.profiles,
+        threads: spec.threads,
+        retries: spec.retries,
+        poll_interval_ms: spec.poll_interval_ms,
+        poll_timeout_secs: spec.poll_timeout_secs,
+        watch_interval_secs: spec.watch_interval_secs,
+        payment_method_id: spec.payment_method_id,
+        max_cancellation_fee: spec.max_cancellation_fee,
+        max_deposit: spec.max_deposit,
+        poll_schedule: spec.poll_schedule,
+        status: JobStatus::Pending,
+        outcome: None,
+    })
+}
+
+impl Job {
+    /// A short, readable id: <venue>-<first date>-<4 hex digits>
+    pub fn new_id(venue_id: &str, dates: &[String]) -> String {
+        let date = dates.first().map(|d| d.replace('-', "")).unwrap_or_default();
+        format!("{}-{}-{:04x}", venue_id, date, fastrand::u16(..))
+    }
+
+    pub fn venue_label(&self) -> String {
+        self.venue_name
+            .clone()
+            .unwrap_or_else(|| format!("venue {}", self.venue_id))
+    }
+
+    /// Booking parameters for one of the job's dates
+    pub fn params(&self, day: &str) -> BookingParams {
+        BookingParams {
+            venue_id: self.venue_id.clone(),
+            party_size: self.party_size,
+            day: day.to_string(),
+            times: self.times.clone(),
+            types: self.types.clone(),
+            dry_run: None,
+            num_threads: self.threads,
+            num_retries: self.retries,
+            retry: Default::default(),
+            poll_interval: std::time::Duration::from_millis(self.poll_interval_ms),
+            poll_timeout: std::time::Duration::from_secs(self.poll_timeout_secs),
+            poll_schedule: self.poll_schedule,
+            release_at: Some(self.release_at),
+            payment_method_id: self.payment_method_id,
+            policy_limits: PolicyLimits {
+                max_cancellation_fee: self.max_cancellation_fee,
+                max_deposit: self.max_deposit,
+            },
+        }
+    }
+
+    pub fn release_local(&self) -> String {
+        self.release_at
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string()
+    }
+}
+
+/// When reservations for `date` open: `release_time` local time, `lead_days` days earlier
+pub fn release_instant(date: &str, lead_days: u32, release_time: NaiveTime) -> Result<DateTime<Utc>> {
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .context(format!("Invalid date '{}': expected YYYY-MM-DD", date))?;
+    let release_date = date - chrono::Duration::days(lead_days as i64);
+    let local = Local
+        .from_local_datetime(&release_date.and_time(release_time))
+        .earliest()
+        .context(format!("{} {} does not exist in the local timezone", release_date, release_time))?;
+    Ok(local.with_timezone(&Utc))
+}
+
+/// When reservations for the earliest of `dates` open
+pub fn earliest_release(dates: &[String], lead_days: u32, release_time: &str) -> Result<DateTime<Utc>> {
+    let release_time = NaiveTime::parse_from_str(release_time, "%H:%M:%S")
+        .context(format!("Invalid time '{}': expected HH:MM:SS", release_time))?;
+    dates
+        .iter()
+        .map(|date| release_instant(date, lead_days, release_time))
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .min()
+        .context("At least one date is required")
+}
+
+/// A job id or venue id with something other than letters, digits, `-` and `_`
+#[derive(Debug)]
+pub struct InvalidId(pub String);
+
+impl std::fmt::Display for InvalidId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid id '{}': only letters, digits, '-' and '_' are allowed", self.0)
+    }
+}
+
+impl std::error::Error for InvalidId {}
+
+/// Ids end up in file names, so only allow `[A-Za-z0-9_-]`
+pub fn validate_id(id: &str) -> Result<()> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(InvalidId(id.to_string()).into());
+    }
+    Ok(())
+}
+
+/// A claim on running a job, held until dropped. The OS releases it if the
+/// process holding it dies, so a crashed run can be picked up again.
+pub struct JobLock {
+    _file: fs::File,
+}
+
+/// Scheduled jobs, one TOML file each
+pub struct JobStore {
+    dir: PathBuf,
+}
+
+impl JobStore {
+    pub fn open_default() -> Self {
+        Self {
+            dir: config::app_dir().join("jobs"),
+        }
+    }
+
+    /// A job's file; ids that could point outside the store are refused
+    fn path(&self, id: &str) -> Result<PathBuf> {
+        validate_id(id)?;
+        Ok(self.dir.join(format!("{}.toml", id)))
+    }
+
+    fn lock_path(&self, id: &str) -> Result<PathBuf> {
+        validate_id(id)?;
+        Ok(self.dir.join(format!("{}.lock", id)))
+    }
+
+    /// Claim a job for this process, or None if another process (the daemon or a
+    /// `schedule run` timer) is running it right now
+    pub fn try_lock(&self, id: &str) -> Result<Option<JobLock>> {
+        fs::create_dir_all(&self.dir).context(format!("Failed to create {}", self.dir.display()))?;
+        let path = self.lock_path(id)?;
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)
+            .context(format!("Failed to open {}", path.display()))?;
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(fs::TryLockError::WouldBlock) => return Ok(None),
+            Err(fs::TryLockError::Error(e)) => {
+                return Err(e).context(format!("Failed to lock {}", path.display()));
+            }
+        }
+
+        // The owner's PID, for anyone wondering who holds it
+        file.set_len(0)?;
+        writeln!(file, "{}", std::process::id())?;
+        Ok(Some(JobLock { _file: file }))
+    }
+
+    /// All jobs, soonest release first
+    pub fn list(&self) -> Result<Vec<Job>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut jobs = Vec::new();
+        for entry in fs::read_dir(&self.dir).context(format!("Failed to read {}", self.dir.display()))? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "toml") {
+                jobs.push(Self::read(&path)?);
+            }
+        }
+        jobs.sort_by_key(|job| job.release_at);
+        Ok(jobs)
+    }
+
+    fn read(path: &PathBuf) -> Result<Job> {
+        let contents = fs::read_to_string(path).context(format!("Failed to read job {}", path.display()))?;
+        toml::from_str(&contents).context(format!("Failed to parse job {}", path.display()))
+    }
+
+    pub fn exists(&self, id: &str) -> bool {
+        self.path(id).is_ok_and(|path| path.exists())
+    }
+
+    pub fn get(&self, id: &str) -> Result<Job> {
+        let path = self.path(id)?;
+        if !path.exists() {
+            anyhow::bail!("No scheduled job '{}' (see `resy-rust schedule list`)", id);
+        }
+        Self::read(&path)
+    }
+
+    pub fn save(&self, job: &Job) -> Result<PathBuf> {
+        fs::create_dir_all(&self.dir).context(format!("Failed to create {}", self.dir.display()))?;
+        let path = self.path(&job.id)?;
+        let contents = toml::to_string_pretty(job).context("Failed to serialize job")?;
+        fs::write(&path, contents).context(format!("Failed to write {}", path.display()))?;
+        Ok(path)
+    }
+
+    pub fn remove(&self, id: &str) -> Result<Job> {
+        let job = self.get(id)?;
+        fs::remove_file(self.path(id)?).context(format!("Failed to remove job '{}'", id))?;
+        let _ = fs::remove_file(self.lock_path(id)?);
+        Ok(job)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn job_lock_is_exclusive_until_dropped() {
+        let dir = std::env::temp_dir().join(format!("resy-rust-jobs-{}", std::process::id()));
+        let store = JobStore { dir: dir.clone() };
+
+        let lock = store.try_lock("job-1").unwrap().expect("first claim succeeds");
+        assert!(store.try_lock("job-1").unwrap().is_none());
+        assert!(store.try_lock("job-2").unwrap().is_some());
+        assert_eq!(fs::read_to_string(dir.join("job-1.lock")).unwrap().trim(), std::process::id().to_string());
+
+        drop(lock);
+        assert!(store.try_lock("job-1").unwrap().is_some());
+        assert!(store.try_lock("../job-1").is_err());
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,240 @@
+use anyhow::{Context, Result};
+use chrono::Local;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+use crate::schedule::Job;
+
+/// Every unit this tool writes starts with this prefix
+const UNIT_PREFIX: &str = "resy-rust-";
+const DAEMON_UNIT: &str = "resy-rust-daemon";
+
+/// ~/.config/systemd/user (or $XDG_CONFIG_HOME/systemd/user)
+pub fn unit_dir() -> PathBuf {
+    let config_home = env::var("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from(env::var("HOME").unwrap_or_else(|_| ".".to_string())).join(".config"));
+    config_home.join("systemd").join("user")
+}
+
+pub fn job_unit_name(id: &str) -> String {
+    format!("{}job-{}", UNIT_PREFIX, id)
+}
+
+pub fn daemon_unit_name() -> String {
+    DAEMON_UNIT.to_string()
+}
+
+/// How to invoke resy-rust from a unit
+pub struct Invocation {
+    pub exe: PathBuf,
+    pub config_path: PathBuf,
+    /// Directory the service runs in, so a .env file there is picked up
+    pub working_dir: PathBuf,
+}
+
+impl Invocation {
+    pub fn current(config_path: &Path) -> Result<Self> {
+        Ok(Self {
+            exe: env::current_exe().context("Failed to locate the resy-rust binary")?,
+            config_path: config_path.to_path_buf(),
+            working_dir: env::current_dir().context("Failed to read the current directory")?,
+        })
+    }
+
+    fn exec_start(&self, args: &[&str]) -> String {
+        let mut parts = vec![
+            quote(&self.exe.to_string_lossy()),
+            "--config".to_string(),
+            quote(&self.config_path.to_string_lossy()),
+        ];
+        parts.extend(args.iter().map(|arg| quote(arg)));
+        parts.join(" ")
+    }
+}
+
+/// Quote an ExecStart argument for systemd
+fn quote(arg: &str) -> String {
+    let escaped = arg.replace('\\', "\\\\").replace('"', "\\\"").replace('%', "%%");
+    if escaped.is_empty() || escaped.contains(char::is_whitespace) || escaped != arg {
+        format!("\"{}\"", escaped)
+    } else {
+        escaped
+    }
+}
+
+/// A service and the timer that starts it
+pub struct UnitPair {
+    pub name: String,
+    pub service: String,
+    pub timer: String,
+}
+
+/// One-shot service that runs a job, started `warmup` before its release
+pub fn job_units(job: &Job, invocation: &Invocation, warmup: Duration) -> Result<UnitPair> {
+    let start = job.release_at - chrono::Duration::from_std(warmup)?;
+    let service = format!(
+        "[Unit]\n\
+         Description=resy-rust booking {id} ({venue} on {dates})\n\
+         After=network-online.target\n\
+         Wants=network-online.target\n\
+         \n\
+         [Service]\n\
+         Type=oneshot\n\
+         WorkingDirectory={dir}\n\
+         ExecStart={exec}\n",
+        id = job.id,
+        venue = job.venue_label(),
+        dates = job.dates.join(", "),
+        dir = invocation.working_dir.display(),
+        exec = invocation.exec_start(&["schedule", "run", &job.id]),
+    );
+    let timer = format!(
+        "[Unit]\n\
+         Description=Start resy-rust booking {id} before its release at {release}\n\
+         \n\
+         [Timer]\n\
+         OnCalendar={start}\n\
+         AccuracySec=1s\n\
+         Persistent=true\n\
+         \n\
+         [Install]\n\
+         WantedBy=timers.target\n",
+        id = job.id,
+        release = job.release_local(),
+        start = start.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S"),
+    );
+
+    Ok(UnitPair {
+        name: job_unit_name(&job.id),
+        service,
+        timer,
+    })
+}
+
+/// Long-running daemon service, started by its timer shortly after login
+pub fn daemon_units(invocation: &Invocation) -> UnitPair {
+    let service = format!(
+        "[Unit]\n\
+         Description=resy-rust daemon for scheduled bookings\n\
+         After=network-online.target\n\
+         Wants=network-online.target\n\
+         \n\
+         [Service]\n\
+         WorkingDirectory={dir}\n\
+         ExecStart={exec}\n\
+         Restart=on-failure\n\
+         RestartSec=10\n",
+        dir = invocation.working_dir.display(),
+        exec = invocation.exec_start(&["daemon"]),
+    );
+    let timer = "[Unit]\n\
+         Description=Start the resy-rust daemon\n\
+         \n\
+         [Timer]\n\
+         OnStartupSec=30s\n\
+         \n\
+         [Install]\n\
+         WantedBy=timers.target\n"
+        .to_string();
+
+    UnitPair {
+        name: daemon_unit_name(),
+        service,
+        timer,
+    }
+}
+
+fn systemctl(args: &[&str]) -> Result<()> {
+    let status = Command::new("systemctl")
+        .arg("--user")
+        .args(args)
+        .status()
+        .context("Failed to run systemctl")?;
+    if !status.success() {
+        anyhow::bail!("`systemctl --user {}` exited with {}", args.join(" "), status);
+    }
+    Ok(())
+}
+
+/// Write the unit files and, if `enable`, start the timer
+pub fn install(units: &UnitPair, enable: bool) -> Result<Vec<PathBuf>> {
+    let dir = unit_dir();
+    fs::create_dir_all(&dir).context(format!("Failed to create {}", dir.display()))?;
+
+    let service_path = dir.join(format!("{}.service", units.name));
+    let timer_path = dir.join(format!("{}.timer", units.name));
+    fs::write(&service_path, &units.service).context(format!("Failed to write {}", service_path.display()))?;
+    fs::write(&timer_path, &units.timer).context(format!("Failed to write {}", timer_path.display()))?;
+
+    if enable {
+        systemctl(&["daemon-reload"])?;
+        systemctl(&["enable", "--now", &format!("{}.timer", units.name)])?;
+    }
+    Ok(vec![service_path, timer_path])
+}
+
+/// An installed service/timer pair
+pub struct InstalledUnit {
+    pub name: String,
+    /// The timer's OnCalendar/OnStartupSec line
+    pub trigger: String,
+}
+
+/// resy-rust timers in the unit directory
+pub fn list() -> Result<Vec<InstalledUnit>> {
+    let dir = unit_dir();
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut units = Vec::new();
+    for entry in fs::read_dir(&dir).context(format!("Failed to read {}", dir.display()))? {
+        let path = entry?.path();
+        let Some(name) = path.file_name().and_then(|n| n.to_str()).and_then(|n| n.strip_suffix(".timer")) else {
+            continue;
+        };
+        if !name.starts_with(UNIT_PREFIX) {
+            continue;
+        }
+        let contents = fs::read_to_string(&path).unwrap_or_default();
+        let trigger = contents
+            .lines()
+            .find(|line| line.starts_with("OnCalendar=") || line.starts_with("OnStartupSec="))
+            .unwrap_or_default()
+            .to_string();
+        units.push(InstalledUnit {
+            name: name.to_string(),
+            trigger,
+        });
+    }
+    units.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(units)
+}
+
+pub fn is_installed(name: &str) -> bool {
+    unit_dir().join(format!("{}.timer", name)).exists()
+}
+
+/// Stop and disable the timer and service (best effort), then delete both files
+pub fn uninstall(name: &str) -> Result<()> {
+    if !is_installed(name) {
+        anyhow::bail!("No unit {} in {}", name, unit_dir().display());
+    }
+
+    let _ = systemctl(&["disable", "--now", &format!("{}.timer", name)]);
+    let _ = systemctl(&["stop", &format!("{}.service", name)]);
+
+    let dir = unit_dir();
+    for suffix in ["timer", "service"] {
+        let path = dir.join(format!("{}.{}", name, suffix));
+        if path.exists() {
+            fs::remove_file(&path).context(format!("Failed to remove {}", path.display()))?;
+        }
+    }
+    let _ = systemctl(&["daemon-reload"]);
+    Ok(())
+}
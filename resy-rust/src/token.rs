@@ -0,0 +1,93 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+/// Warn when a token has less than this much validity left
+pub const EXPIRY_WARNING_HOURS: i64 = 24;
+
+#[derive(Debug, Deserialize)]
+struct Claims {
+    exp: Option<i64>,
+}
+
+/// Decode the `exp` claim of a JWT auth token locally (no signature check, no network call)
+pub fn expiry(token: &str) -> Option<DateTime<Utc>> {
+    let token = token.trim().trim_matches('"').trim_matches('\'');
+    let payload = token.split('.').nth(1)?;
+    let bytes = URL_SAFE_NO_PAD.decode(payload.trim_end_matches('=')).ok()?;
+    let claims: Claims = serde_json::from_slice(&bytes).ok()?;
+    DateTime::from_timestamp(claims.exp?, 0)
+}
+
+/// Validity of an auth token at a given instant
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenStatus {
+    /// Token isn't a JWT or has no `exp` claim
+    Unknown,
+    Valid { expires_at: DateTime<Utc> },
+    ExpiringSoon { expires_at: DateTime<Utc> },
+    Expired { expires_at: DateTime<Utc> },
+}
+
+impl TokenStatus {
+    pub fn at(token: &str, instant: DateTime<Utc>) -> Self {
+        let Some(expires_at) = expiry(token) else {
+            return Self::Unknown;
+        };
+
+        let remaining = expires_at - instant;
+        if remaining <= chrono::Duration::zero() {
+            Self::Expired { expires_at }
+        } else if remaining < chrono::Duration::hours(EXPIRY_WARNING_HOURS) {
+            Self::ExpiringSoon { expires_at }
+        } else {
+            Self::Valid { expires_at }
+        }
+    }
+
+    pub fn now(token: &str) -> Self {
+        Self::at(token, Utc::now())
+    }
+
+    pub fn expires_at(&self) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Unknown => None,
+            Self::Valid { expires_at }
+            | Self::ExpiringSoon { expires_at }
+            | Self::Expired { expires_at } => Some(*expires_at),
+        }
+    }
+
+    /// One-line summary, e.g. "valid for 12d 4h (expires 2025-11-02 09:14 UTC)"
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Unknown => "expiry unknown (token is not a JWT)".to_string(),
+            Self::Valid { expires_at } | Self::ExpiringSoon { expires_at } => format!(
+                "valid for {} (expires {})",
+                format_remaining(*expires_at - Utc::now()),
+                expires_at.format("%Y-%m-%d %H:%M UTC")
+            ),
+            Self::Expired { expires_at } => format!(
+                "expired {} ago ({})",
+                format_remaining(Utc::now() - *expires_at),
+                expires_at.format("%Y-%m-%d %H:%M UTC")
+            ),
+        }
+    }
+}
+
+/// Compact human-readable duration: "3d 4h", "5h 12m", "42m", "17s"
+pub fn format_remaining(duration: chrono::Duration) -> String {
+    let secs = duration.num_seconds().max(0);
+    let (days, hours, minutes) = (secs / 86_400, (secs % 86_400) / 3_600, (secs % 3_600) / 60);
+
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m", minutes)
+    } else {
+        format!("{}s", secs)
+    }
+}
@@ -1,3 +1,5 @@
+use anyhow::Result;
+use chrono::Datelike;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize)]
@@ -43,6 +45,9 @@ pub struct SlotConfig {
 #[derive(Debug, Deserialize)]
 pub struct VenueResponse {
     pub venue: VenueInfo,
+    /// How many days ahead the venue opens reservations
+    #[serde(default)]
+    pub lead_time_in_days: Option<u32>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -50,10 +55,72 @@ pub struct VenueInfo {
     pub name: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct LoginResponse {
+    pub token: String,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UserResponse {
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub em_address: Option<String>,
+    pub payment_methods: Option<Vec<PaymentMethod>>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct DetailsResponse {
     pub book_token: BookToken,
     pub user: User,
+    pub cancellation: Option<Cancellation>,
+    pub payment: Option<Payment>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Cancellation {
+    pub fee: Option<CancellationFee>,
+    pub display: Option<CancellationDisplay>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CancellationFee {
+    /// Fee charged per person for late cancellations and no-shows
+    pub amount: Option<f64>,
+    pub date_cut_off: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CancellationDisplay {
+    #[serde(default)]
+    pub policy: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Payment {
+    pub amounts: Option<PaymentAmounts>,
+    pub config: Option<PaymentConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PaymentAmounts {
+    /// Deposit or prepayment charged at booking time
+    pub reservation_charge: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PaymentConfig {
+    /// e.g. "free", "deposit", "ticket"
+    #[serde(rename = "type")]
+    pub payment_type: Option<String>,
+}
+
+/// Confirmation returned by /3/book
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct BookResponse {
+    pub resy_token: Option<String>,
+    pub reservation_id: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -66,9 +133,90 @@ pub struct User {
     pub payment_methods: Option<Vec<PaymentMethod>>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Clone)]
 pub struct PaymentMethod {
     pub id: u64,
+    /// Card brand, e.g. "Visa"
+    #[serde(rename = "type")]
+    pub brand: Option<String>,
+    /// Last four digits of the card
+    #[serde(rename = "display")]
+    pub last4: Option<String>,
+    pub exp_month: Option<u32>,
+    pub exp_year: Option<i32>,
+    #[serde(default)]
+    pub is_default: bool,
+}
+
+impl PaymentMethod {
+    /// Cards are valid through the last day of their expiry month
+    pub fn is_expired(&self) -> bool {
+        let (Some(month), Some(year)) = (self.exp_month, self.exp_year) else {
+            return false;
+        };
+        let today = chrono::Local::now().date_naive();
+        (year, month) < (today.year(), today.month())
+    }
+
+    /// e.g. "Visa •••• 4242, exp 04/2027"
+    pub fn describe(&self) -> String {
+        let mut description = format!(
+            "{} •••• {}",
+            self.brand.as_deref().unwrap_or("Card"),
+            self.last4.as_deref().unwrap_or("????")
+        );
+        if let (Some(month), Some(year)) = (self.exp_month, self.exp_year) {
+            description.push_str(&format!(", exp {:02}/{}", month, year));
+        }
+        description
+    }
+}
+
+/// The account can't pay with the requested (or default) card. Retrying won't
+/// change that, so the account's booking threads stop.
+#[derive(Debug)]
+pub struct PaymentMethodUnusable(pub String);
+
+impl std::fmt::Display for PaymentMethodUnusable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for PaymentMethodUnusable {}
+
+impl User {
+    /// The payment method to book with: `requested` if given, otherwise the
+    /// account's default card (or the first unexpired card when none is flagged default)
+    pub fn select_payment_method(&self, requested: Option<u64>) -> Result<Option<&PaymentMethod>> {
+        let methods = self.payment_methods.as_deref().unwrap_or_default();
+
+        let method = match requested {
+            Some(id) => Some(
+                methods
+                    .iter()
+                    .find(|method| method.id == id)
+                    .ok_or_else(|| PaymentMethodUnusable(format!("Payment method {} not found on this account", id)))?,
+            ),
+            None => methods
+                .iter()
+                .find(|method| method.is_default)
+                .or_else(|| methods.iter().find(|method| !method.is_expired())),
+        };
+
+        if let Some(method) = method {
+            if method.is_expired() {
+                return Err(PaymentMethodUnusable(format!(
+                    "Payment method {} ({}) has expired",
+                    method.id,
+                    method.describe()
+                ))
+                .into());
+            }
+        }
+
+        Ok(method)
+    }
 }
 
 impl Slot {
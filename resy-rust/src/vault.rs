@@ -0,0 +1,144 @@
+use anyhow::{Context, Result};
+use argon2::Argon2;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::ChaCha20Poly1305;
+use chacha20poly1305::aead::rand_core::RngCore;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::config;
+
+const VAULT_VERSION: u32 = 1;
+const SALT_LEN: usize = 16;
+
+/// Secrets stored inside an encrypted credentials file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultContents {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    pub auth_token: String,
+}
+
+/// On-disk format: Argon2id-derived key, ChaCha20-Poly1305 ciphertext
+#[derive(Debug, Serialize, Deserialize)]
+struct VaultFile {
+    version: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<chacha20poly1305::Key> {
+    let mut key = chacha20poly1305::Key::default();
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| anyhow::anyhow!("Failed to derive key from passphrase: {}", e))?;
+    Ok(key)
+}
+
+pub fn encrypt(contents: &VaultContents, passphrase: &str, path: &Path) -> Result<()> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+
+    let cipher = ChaCha20Poly1305::new(&derive_key(passphrase, &salt)?);
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let plaintext = serde_json::to_vec(contents)?;
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext.as_ref())
+        .map_err(|_| anyhow::anyhow!("Failed to encrypt credentials"))?;
+
+    let file = VaultFile {
+        version: VAULT_VERSION,
+        salt: BASE64.encode(salt),
+        nonce: BASE64.encode(nonce),
+        ciphertext: BASE64.encode(ciphertext),
+    };
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    config::write_private(path, &serde_json::to_string_pretty(&file)?)
+        .context(format!("Failed to write credentials file: {}", path.display()))
+}
+
+pub fn decrypt(passphrase: &str, path: &Path) -> Result<VaultContents> {
+    let raw = fs::read_to_string(path)
+        .context(format!("Failed to read credentials file: {}", path.display()))?;
+    let file: VaultFile = serde_json::from_str(&raw)
+        .context(format!("Malformed credentials file: {}", path.display()))?;
+
+    if file.version != VAULT_VERSION {
+        anyhow::bail!("Unsupported credentials file version: {}", file.version);
+    }
+
+    let salt = BASE64.decode(&file.salt).context("Malformed salt")?;
+    let nonce = BASE64.decode(&file.nonce).context("Malformed nonce")?;
+    let ciphertext = BASE64.decode(&file.ciphertext).context("Malformed ciphertext")?;
+    if nonce.len() != 12 {
+        anyhow::bail!("Malformed nonce");
+    }
+
+    let cipher = ChaCha20Poly1305::new(&derive_key(passphrase, &salt)?);
+    let plaintext = cipher
+        .decrypt(chacha20poly1305::Nonce::from_slice(&nonce), ciphertext.as_ref())
+        .map_err(|_| anyhow::anyhow!("Failed to decrypt {}: wrong passphrase?", path.display()))?;
+
+    serde_json::from_slice(&plaintext).context("Failed to parse decrypted credentials")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vault_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("resy-rust-vault-{}-{}", std::process::id(), name)).join("credentials.enc")
+    }
+
+    fn contents() -> VaultContents {
+        VaultContents { api_key: Some("key".to_string()), auth_token: "token".to_string() }
+    }
+
+    #[test]
+    fn round_trip_returns_the_same_secrets() {
+        let path = vault_path("round-trip");
+        encrypt(&contents(), "correct horse", &path).unwrap();
+
+        let decrypted = decrypt("correct horse", &path).unwrap();
+        assert_eq!(decrypted.api_key.as_deref(), Some("key"));
+        assert_eq!(decrypted.auth_token, "token");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn wrong_passphrase_is_rejected() {
+        let path = vault_path("wrong-passphrase");
+        encrypt(&contents(), "correct horse", &path).unwrap();
+
+        let error = decrypt("battery staple", &path).unwrap_err();
+        assert!(error.to_string().contains("wrong passphrase"), "{}", error);
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn tampered_ciphertext_is_rejected() {
+        let path = vault_path("tampered");
+        encrypt(&contents(), "correct horse", &path).unwrap();
+
+        let mut file: VaultFile = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        let mut ciphertext = BASE64.decode(&file.ciphertext).unwrap();
+        ciphertext[0] ^= 0x01;
+        file.ciphertext = BASE64.encode(ciphertext);
+        fs::write(&path, serde_json::to_string(&file).unwrap()).unwrap();
+
+        assert!(decrypt("correct horse", &path).is_err());
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+}
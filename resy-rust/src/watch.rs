@@ -0,0 +1,264 @@
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use crate::api::{BookingParams, DryRun, ResyClient};
+use crate::breaker;
+use crate::notify::Event;
+use crate::ratelimit::BudgetExhausted;
+use crate::types::{PaymentMethodUnusable, Slot};
+use crate::LoggerHandle;
+
+/// Longest pause between checks while the API keeps failing
+const MAX_ERROR_BACKOFF: Duration = Duration::from_secs(15 * 60);
+
+/// Log a "still watching" line this often
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// One venue/date pair to watch, written as VENUE_ID:YYYY-MM-DD
+#[derive(Debug, Clone)]
+pub struct WatchTarget {
+    pub venue_id: String,
+    pub day: String,
+}
+
+impl FromStr for WatchTarget {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (venue_id, day) = s
+            .split_once(':')
+            .context(format!("Invalid target '{}': expected VENUE_ID:YYYY-MM-DD", s))?;
+        chrono::NaiveDate::parse_from_str(day, "%Y-%m-%d")
+            .context(format!("Invalid date in target '{}': expected YYYY-MM-DD", s))?;
+
+        Ok(Self {
+            venue_id: venue_id.to_string(),
+            day: day.to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WatchOptions {
+    pub targets: Vec<WatchTarget>,
+    /// Party size, times, types and booking settings shared by every target
+    pub template: BookingParams,
+    /// Base delay between rounds of checks
+    pub interval: Duration,
+    /// Random +/- fraction applied to each delay, e.g. 0.2 for ±20%
+    pub jitter: f64,
+    /// Only report matching slots instead of booking them
+    pub alert_only: bool,
+    /// Stop after this long (default: run until booked or interrupted)
+    pub duration: Option<Duration>,
+}
+
+struct TargetState {
+    target: WatchTarget,
+    name: String,
+    booked: bool,
+    /// Slots already reported, so each sighting is notified once
+    seen: HashSet<String>,
+}
+
+impl TargetState {
+    fn params(&self, template: &BookingParams) -> BookingParams {
+        BookingParams {
+            venue_id: self.target.venue_id.clone(),
+            day: self.target.day.clone(),
+            ..template.clone()
+        }
+    }
+}
+
+fn slot_key(slot: &Slot) -> String {
+    format!("{}|{}", slot.date.start, slot.config.slot_type)
+}
+
+/// `base` scaled by a random factor in [1 - jitter, 1 + jitter]
+fn jittered(base: Duration, jitter: f64) -> Duration {
+    let jitter = jitter.clamp(0.0, 1.0);
+    let factor = 1.0 + jitter * (fastrand::f64() * 2.0 - 1.0);
+    base.mul_f64(factor)
+}
+
+/// Log and notify that the watch stopped, and return the error that stopped it
+fn stop(client: &ResyClient, params: &BookingParams, name: &str, checks: usize, error: anyhow::Error, logger: &LoggerHandle) -> anyhow::Error {
+    logger.log(&format!("⏹️  Watch stopped after {} checks: {}", checks, error));
+    client.notifier().emit(Event::failed(params, name, &error));
+    error
+}
+
+/// Poll targets at a low, jittered rate for hours or days, booking (or just
+/// reporting) matching slots as cancellations appear. Transient API and
+/// network failures back off and are retried rather than ending the watch;
+/// rejected credentials and other errors the retry policy won't retry end it.
+pub async fn watch(clients: &[ResyClient], options: WatchOptions, logger: LoggerHandle) -> Result<()> {
+    let client = clients[0].live_account(clients)?;
+    let mut states = Vec::new();
+
+    for target in &options.targets {
+        let name = match client.fetch_venue_details(&target.venue_id).await {
+            Ok(venue) => venue.venue.name,
+            // Other accounts may still work; checks switch to them
+            Err(e) if breaker::is_fatal(&e) && clients[0].live_account(clients).is_err() => {
+                let params = BookingParams {
+                    venue_id: target.venue_id.clone(),
+                    day: target.day.clone(),
+                    ..options.template.clone()
+                };
+                return Err(stop(client, &params, &target.venue_id, 0, e, &logger));
+            }
+            Err(e) => {
+                logger.log(&format!("⚠️  Could not fetch venue {}: {}", target.venue_id, e));
+                format!("venue {}", target.venue_id)
+            }
+        };
+        logger.log(&format!("👀 Watching {} on {}", name, target.day));
+        states.push(TargetState {
+            target: target.clone(),
+            name,
+            booked: false,
+            seen: HashSet::new(),
+        });
+    }
+
+    let start = Instant::now();
+    let mut last_heartbeat = Instant::now();
+    let mut checks = 0usize;
+    let mut consecutive_errors = 0u32;
+
+    loop {
+        for state in states.iter_mut().filter(|s| !s.booked) {
+            checks += 1;
+            let params = state.params(&options.template);
+            // Check with the first account whose credentials still work
+            let client = match clients[0].live_account(clients) {
+                Ok(client) => client,
+                Err(e) => return Err(stop(&clients[0], &params, &state.name, checks, e, &logger)),
+            };
+
+            let slots = match client.fetch_slots(&params.venue_id, params.party_size, &params.day).await {
+                Ok(slots) => {
+                    consecutive_errors = 0;
+                    slots
+                }
+                Err(e) if breaker::is_fatal(&e) && clients[0].live_account(clients).is_ok() => {
+                    logger.log(&format!("🔀 {}; checking with another account", e));
+                    continue;
+                }
+                Err(e)
+                    if e.downcast_ref::<BudgetExhausted>().is_some()
+                        || breaker::is_fatal(&e)
+                        || !params.retry.is_retryable(&e) =>
+                {
+                    return Err(stop(client, &params, &state.name, checks, e, &logger));
+                }
+                Err(e) => {
+                    consecutive_errors += 1;
+                    // Log the first failure of a streak, then every 10th
+                    if consecutive_errors == 1 || consecutive_errors.is_multiple_of(10) {
+                        logger.log(&format!("⚠️  {} on {}: {} ({} consecutive errors)",
+                            state.name, params.day, e, consecutive_errors));
+                    }
+                    continue;
+                }
+            };
+
+            let matching: Vec<_> = slots
+                .into_iter()
+                .filter(|slot| slot.matches(&params.times, &params.types))
+                .collect();
+
+            let current: HashSet<_> = matching.iter().map(slot_key).collect();
+            let new_slots: Vec<_> = matching
+                .iter()
+                .filter(|slot| !state.seen.contains(&slot_key(slot)))
+                .cloned()
+                .collect();
+            // Forget slots that disappeared so they're reported again if they return
+            state.seen = current;
+            if !new_slots.is_empty() {
+                client.notifier().emit(Event::slots_found(&params, &state.name, &new_slots));
+            }
+
+            if options.alert_only {
+                for slot in &new_slots {
+                    logger.log(&format!("🔔 {} on {}: {} ({}) is available",
+                        state.name, params.day, slot.date.start, slot.config.slot_type));
+                }
+                continue;
+            }
+
+            if matching.is_empty() {
+                continue;
+            }
+
+            logger.log(&format!("🎯 {} on {}: found {} matching slots", state.name, params.day, matching.len()));
+            for slot in &matching {
+                logger.log(&format!("   - {} ({})", slot.date.start, slot.config.slot_type));
+            }
+
+            if let Some(mode) = params.dry_run {
+                if mode == DryRun::Deep {
+                    if let Err(e) = client.deep_dry_run(&params, &matching, &logger).await {
+                        logger.log(&format!("⚠️  {}", e));
+                    }
+                }
+                logger.log("🏃 Dry run mode - skipping actual booking");
+                state.booked = true;
+                continue;
+            }
+
+            match client.book_slots(&params, &state.name, matching, clients, logger.clone()).await {
+                Ok(_) => {
+                    logger.log(&format!("🎉 Booked {} on {}", state.name, params.day));
+                    state.booked = true;
+                }
+                // book_slots already sent the failure
+                Err(e) if e.is::<PaymentMethodUnusable>() => {
+                    logger.log(&format!("⏹️  Watch stopped after {} checks: {}", checks, e));
+                    return Err(e);
+                }
+                Err(e) => logger.log(&format!("⚠️  {} — continuing to watch", e)),
+            }
+        }
+
+        if states.iter().all(|s| s.booked) {
+            return Ok(());
+        }
+
+        if options.duration.is_some_and(|duration| start.elapsed() >= duration) {
+            logger.log(&format!("⏹️  Watch ended after {:.1}h ({} checks)",
+                start.elapsed().as_secs_f64() / 3600.0, checks));
+            break;
+        }
+
+        if last_heartbeat.elapsed() >= HEARTBEAT_INTERVAL {
+            logger.log(&format!("⏳ Still watching... ({:.1}h elapsed, {} checks)",
+                start.elapsed().as_secs_f64() / 3600.0, checks));
+            last_heartbeat = Instant::now();
+        }
+
+        // Back off exponentially while the API is failing
+        let backoff = options.interval.saturating_mul(2u32.saturating_pow(consecutive_errors.min(16)));
+        let delay = jittered(backoff.min(MAX_ERROR_BACKOFF.max(options.interval)), options.jitter);
+
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = tokio::signal::ctrl_c() => {
+                logger.log(&format!("⏹️  Watch interrupted after {:.1}h ({} checks)",
+                    start.elapsed().as_secs_f64() / 3600.0, checks));
+                break;
+            }
+        }
+    }
+
+    if options.alert_only || states.iter().any(|s| s.booked) {
+        Ok(())
+    } else {
+        anyhow::bail!("❌ Watch ended without booking")
+    }
+}
@@ -0,0 +1,190 @@
+use anyhow::{Context, Result};
+use reqwest::{header, Client};
+use std::time::Duration;
+use tokio::time::sleep;
+
+use crate::config::WebhookConfig;
+use crate::notify::{render_template, Event, EventKind};
+
+/// First retry delay; doubles on each further attempt
+const BASE_RETRY_DELAY: Duration = Duration::from_millis(500);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// POSTs events as JSON to a webhook URL
+pub struct WebhookSink {
+    config: WebhookConfig,
+    client: Client,
+}
+
+impl WebhookSink {
+    pub fn new(config: WebhookConfig) -> Result<Self> {
+        let mut headers = header::HeaderMap::new();
+        for (name, value) in &config.headers {
+            headers.insert(
+                header::HeaderName::from_bytes(name.as_bytes())
+                    .context(format!("Invalid webhook header name: {}", name))?,
+                value
+                    .parse()
+                    .context(format!("Invalid value for webhook header {}", name))?,
+            );
+        }
+
+        let client = Client::builder()
+            .default_headers(headers)
+            .timeout(REQUEST_TIMEOUT)
+            .build()?;
+
+        Ok(Self { config, client })
+    }
+
+    pub fn name(&self) -> String {
+        format!("webhook {}", self.config.url)
+    }
+
+    pub fn accepts(&self, kind: EventKind) -> bool {
+        self.config.events.is_empty() || self.config.events.contains(&kind)
+    }
+
+    fn payload(&self, event: &Event) -> Result<String> {
+        match &self.config.template {
+            Some(template) => Ok(render_template(template, event)),
+            None => serde_json::to_string(event).context("Failed to serialize event"),
+        }
+    }
+
+    /// POST the event, retrying network errors, 429s and 5xx with exponential backoff
+    pub async fn deliver(&self, event: &Event) -> Result<()> {
+        let payload = self.payload(event)?;
+        let mut attempt = 0;
+
+        loop {
+            let result = self
+                .client
+                .post(&self.config.url)
+                .header("content-type", "application/json")
+                .body(payload.clone())
+                .send()
+                .await;
+
+            let error = match result {
+                Ok(response) if response.status().is_success() => return Ok(()),
+                Ok(response) => {
+                    let status = response.status();
+                    if !(status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS) {
+                        anyhow::bail!("Webhook returned {}", status);
+                    }
+                    anyhow::anyhow!("Webhook returned {}", status)
+                }
+                Err(e) => anyhow::Error::new(e).context("Failed to reach webhook"),
+            };
+
+            if attempt >= self.config.max_retries {
+                return Err(error.context(format!("Giving up after {} attempts", attempt + 1)));
+            }
+            sleep(BASE_RETRY_DELAY.saturating_mul(2u32.saturating_pow(attempt))).await;
+            attempt += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    /// Read one HTTP request and return its body
+    async fn read_request(stream: &mut TcpStream) -> String {
+        let mut buf = Vec::new();
+        let mut chunk = [0u8; 1024];
+        loop {
+            let n = stream.read(&mut chunk).await.unwrap();
+            assert!(n > 0, "connection closed before the request was complete");
+            buf.extend_from_slice(&chunk[..n]);
+
+            let text = String::from_utf8_lossy(&buf);
+            let Some(header_end) = text.find("\r\n\r\n") else {
+                continue;
+            };
+            let length = text[..header_end]
+                .lines()
+                .find_map(|line| {
+                    let (name, value) = line.split_once(':')?;
+                    name.eq_ignore_ascii_case("content-length")
+                        .then(|| value.trim().parse::<usize>().ok())?
+                })
+                .unwrap_or(0);
+            if buf.len() >= header_end + 4 + length {
+                return text[header_end + 4..header_end + 4 + length].to_string();
+            }
+        }
+    }
+
+    async fn respond(stream: &mut TcpStream, status: &str) {
+        let response = format!("HTTP/1.1 {}\r\ncontent-length: 0\r\nconnection: close\r\n\r\n", status);
+        stream.write_all(response.as_bytes()).await.unwrap();
+        stream.shutdown().await.unwrap();
+    }
+
+    fn sink(url: String, template: Option<&str>) -> WebhookSink {
+        WebhookSink::new(WebhookConfig {
+            url,
+            events: Vec::new(),
+            template: template.map(str::to_string),
+            headers: BTreeMap::new(),
+            max_retries: 1,
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn deliver_retries_server_errors_and_posts_the_event() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+
+        let server = tokio::spawn(async move {
+            let mut bodies = Vec::new();
+            for status in ["500 Internal Server Error", "200 OK"] {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                bodies.push(read_request(&mut stream).await);
+                respond(&mut stream, status).await;
+            }
+            bodies
+        });
+
+        let event = Event::sample(EventKind::Booked);
+        sink(url, None).deliver(&event).await.unwrap();
+
+        let bodies = server.await.unwrap();
+        assert_eq!(bodies.len(), 2);
+        assert_eq!(bodies[0], bodies[1]);
+        let payload: serde_json::Value = serde_json::from_str(&bodies[0]).unwrap();
+        assert_eq!(payload["event"], "booked");
+        assert_eq!(payload["venue_id"], "12345");
+        assert_eq!(payload["confirmation"]["reservation_id"], 123456789);
+    }
+
+    #[tokio::test]
+    async fn deliver_gives_up_on_client_errors_without_retrying() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let body = read_request(&mut stream).await;
+            respond(&mut stream, "400 Bad Request").await;
+            body
+        });
+
+        let event = Event::sample(EventKind::Failed);
+        let error = sink(url, Some(r#"{"text": "{{summary}}"}"#))
+            .deliver(&event)
+            .await
+            .unwrap_err();
+
+        assert!(error.to_string().contains("400"), "{:#}", error);
+        let payload: serde_json::Value = serde_json::from_str(&server.await.unwrap()).unwrap();
+        assert_eq!(payload["text"], event.summary);
+    }
+}